@@ -1,13 +1,95 @@
-use crate::jira::types::JiraIssueKey;
-use clap::{Parser, Subcommand};
+use crate::{
+    completions::issue_candidates, config::Deployment, jira::types::JiraIssueKey,
+    output::OutputFormat,
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
 use std::path::PathBuf;
 
+/// Environment variable holding a Tempo API token
+pub const TEMPO_TOKEN_ENV: &str = "TEMPOMAT_TEMPO_TOKEN";
+
+/// Environment variable holding the profile to use
+pub const PROFILE_ENV: &str = "TEMPOMAT_PROFILE";
+
+/// A duration given on the command line, days and weeks depend on the workday length
+#[derive(Clone, Debug, PartialEq)]
+pub struct DurationArg {
+    /// Part of the duration that does not depend on the workday length
+    pub seconds: f64,
+    /// Amount of workdays, a week has 5 workdays
+    pub workdays: f64,
+    /// The duration as it was entered
+    pub text: String,
+}
+
+impl DurationArg {
+    /// Total amount of seconds, given the seconds in a workday
+    pub fn seconds(&self, workday: usize) -> usize {
+        (self.seconds + self.workdays * workday as f64).round() as usize
+    }
+}
+
+/// Time to log, either a duration or a range of time
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogTime {
+    Duration(DurationArg),
+    Range(NaiveTime, NaiveTime),
+}
+
+/// Remaining estimate given on the command line
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemainingArg {
+    /// Subtract the logged time from the current estimate
+    Auto,
+    Duration(DurationArg),
+}
+
+/// A day given on the command line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DateArg {
+    Date(NaiveDate),
+    Today,
+    Yesterday,
+    /// The last time this weekday occured, today included
+    Weekday(Weekday),
+}
+
+impl DateArg {
+    pub fn resolve(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Date(date) => *date,
+            Self::Today => today,
+            Self::Yesterday => today - Duration::days(1),
+            Self::Weekday(weekday) => {
+                let days_ago = (today.weekday().num_days_from_monday() + 7
+                    - weekday.num_days_from_monday())
+                    % 7;
+                today - Duration::days(days_ago as i64)
+            }
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct TempomatCLI {
     /// Override configuration root path value, can also be override using $TEMPOMAT_ROOT
     #[arg(long)]
     pub config: Option<PathBuf>,
+    /// Profile to use, can also be set using $TEMPOMAT_PROFILE
+    #[arg(long, global = true, env = PROFILE_ENV)]
+    pub profile: Option<String>,
+    /// Show what would be sent to Tempo without sending it, timers are not changed either
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Git repository to read the branch and commits from, instead of the current directory
+    #[arg(long, global = true)]
+    pub repo: Option<PathBuf>,
+    /// How to print the results
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub output: OutputFormat,
     #[command(subcommand)]
     pub command: CLISubcommand,
 }
@@ -16,26 +98,57 @@ pub struct TempomatCLI {
 pub enum CLISubcommand {
     /// Create a new time log
     Log {
-        /// Amount of time to log (XhYmZs)
-        #[arg(value_parser = parsers::parse_arg)]
-        time: (usize, String),
+        /// Amount of time to log (eg. 1h30m, 1h 30m, 1.5h, 1d, 1:30 or 90 minutes) or a range of
+        /// time (eg. 09:00-10:30)
+        #[arg(value_parser = parsers::parse_log_time)]
+        time: LogTime,
+        /// Time of day the worklog started at (HH:MM[:SS]), defaults to the duration before now
+        #[arg(long, value_parser = parsers::parse_time_of_day)]
+        at: Option<NaiveTime>,
+        /// Day of the worklog (YYYY-MM-DD, today, yesterday or a weekday like monday)
+        #[arg(long, value_parser = parsers::parse_date)]
+        date: Option<DateArg>,
         /// Description of the time log
         #[arg(short, long)]
         description: Option<String>,
+        /// Work attribute value (eg. --attr Billable=true), overrides the defaults in config.ron
+        #[arg(long = "attr", value_name = "KEY=VALUE", value_parser = parsers::parse_attribute)]
+        attributes: Vec<(String, String)>,
+        /// Account to log the time to
+        #[arg(long)]
+        account: Option<String>,
+        /// Billable time (same format as durations), defaults to the logged time
+        #[arg(long, value_parser = parsers::parse_arg)]
+        billable: Option<DurationArg>,
+        /// New remaining estimate of the issue, or 'auto' to subtract the logged time from it
+        #[arg(long, value_parser = parsers::parse_remaining)]
+        remaining: Option<RemainingArg>,
         /// Jira issue ID to log to
-        #[arg(short, long, value_parser = parsers::parse_issue_id)]
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
         issue: Option<JiraIssueKey>,
     },
     /// Log in to Tempo and Jira
     Login {
         /// Name of the atlassian instance you have tempo installed to
+        #[arg(long, required_unless_present = "jira_url")]
+        atlassian_instance: Option<String>,
+        /// Base URL of Jira, for self-hosted instances (eg. https://jira.example.com)
         #[arg(long)]
-        atlassian_instance: String,
+        jira_url: Option<String>,
+        /// Base URL of the Tempo API, defaults to Tempo Cloud or the Jira URL on servers
+        #[arg(long)]
+        tempo_url: Option<String>,
+        /// Whether Jira is hosted on Atlassian Cloud or is a Jira Server/Data Center
+        #[arg(long, value_enum, default_value_t)]
+        deployment: Deployment,
+        /// Use a Tempo API token instead of logging in with OAuth
+        #[arg(long, env = TEMPO_TOKEN_ENV)]
+        tempo_token: Option<String>,
     },
     /// Start a new timer
     Start {
         /// Issue to start a timer for
-        #[arg(short, long, value_parser = parsers::parse_issue_id)]
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
         issue: Option<JiraIssueKey>,
     },
     /// End a timer
@@ -46,8 +159,53 @@ pub enum CLISubcommand {
         /// Description of the time log
         #[arg(short, long)]
         description: Option<String>,
+        /// Work attribute value (eg. --attr Billable=true), overrides the defaults in config.ron
+        #[arg(long = "attr", value_name = "KEY=VALUE", value_parser = parsers::parse_attribute)]
+        attributes: Vec<(String, String)>,
+        /// Account to log the time to
+        #[arg(long)]
+        account: Option<String>,
+        /// Billable time (same format as durations), defaults to the logged time
+        #[arg(long, value_parser = parsers::parse_arg)]
+        billable: Option<DurationArg>,
+        /// New remaining estimate of the issue, or 'auto' to subtract the logged time from it
+        #[arg(long, value_parser = parsers::parse_remaining)]
+        remaining: Option<RemainingArg>,
+        /// Issue of the timer
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
+        issue: Option<JiraIssueKey>,
+    },
+    /// Stop and submit the running timer and start one for another issue at the same time
+    Switch {
+        /// Issue to start a timer for
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
+        issue: Option<JiraIssueKey>,
+        /// Issue of the timer to stop, needed if more than one timer is running
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
+        from: Option<JiraIssueKey>,
+        /// Keep the current timer running, to have timers running in parallel
+        #[arg(short, long, conflicts_with_all = ["from", "description"])]
+        keep_running: bool,
+        /// Description of the time log
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// Pause a running timer, paused time is not logged
+    Pause {
+        /// Pause all running timers
+        #[arg(short, long, conflicts_with = "issue")]
+        all: bool,
         /// Issue of the timer
-        #[arg(short, long, value_parser = parsers::parse_issue_id)]
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
+        issue: Option<JiraIssueKey>,
+    },
+    /// Resume a paused timer
+    Resume {
+        /// Resume all paused timers
+        #[arg(short, long, conflicts_with = "issue")]
+        all: bool,
+        /// Issue of the timer
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
         issue: Option<JiraIssueKey>,
     },
     /// List currently running timers
@@ -56,16 +214,228 @@ pub enum CLISubcommand {
         #[arg(short, long)]
         all: bool,
         /// List only from this specific issue
-        #[arg(short, long, value_parser = parsers::parse_issue_id)]
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
         issue: Option<JiraIssueKey>,
     },
+    /// List the issues to pick from when the issue cannot be detected from the branch
+    Issues {
+        /// Search these issues instead of the ones matching `issue_jql` in config.ron
+        #[arg(long)]
+        jql: Option<String>,
+    },
+    /// Show your Tempo worklogs, defaults to today
+    Worklogs {
+        /// First day to show (YYYY-MM-DD)
+        #[arg(long, conflicts_with_all = ["week", "yesterday"])]
+        from: Option<NaiveDate>,
+        /// Last day to show (YYYY-MM-DD), defaults to today
+        #[arg(long, conflicts_with_all = ["week", "yesterday"])]
+        to: Option<NaiveDate>,
+        /// Show the whole current week
+        #[arg(short, long, conflicts_with = "yesterday")]
+        week: bool,
+        /// Show yesterday's worklogs
+        #[arg(short, long)]
+        yesterday: bool,
+    },
+    /// Compare the logged time with the time required by your Tempo schedule, defaults to today
+    Report {
+        /// Report the whole current week
+        #[arg(short, long)]
+        week: bool,
+    },
+    /// Change an existing worklog
+    Edit {
+        /// ID of the Tempo worklog
+        id: usize,
+        /// New amount of time (eg. 1h30m, 1.5h or 1:30)
+        #[arg(short, long, value_parser = parsers::parse_arg)]
+        time: Option<DurationArg>,
+        /// New start date (YYYY-MM-DD, today, yesterday or a weekday like monday)
+        #[arg(long, value_parser = parsers::parse_date)]
+        date: Option<DateArg>,
+        /// New start time (HH:MM[:SS])
+        #[arg(long, value_parser = parsers::parse_time_of_day)]
+        at: Option<NaiveTime>,
+        /// New description
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Move the worklog to another Jira issue
+        #[arg(short, long, value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
+        issue: Option<JiraIssueKey>,
+    },
+    /// Delete an existing worklog
+    Delete {
+        /// ID of the Tempo worklog
+        id: usize,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Delete the last submitted worklog, restoring its timer if it came from one
+    Undo,
+    /// Submit the worklogs that failed or were created while offline
+    Sync {
+        /// Only show the queued worklogs
+        #[arg(short, long)]
+        list: bool,
+        /// Remove the queued worklog at this position (as shown by '--list') without submitting it
+        #[arg(short, long, conflicts_with = "list")]
+        discard: Option<usize>,
+    },
+    /// Print the shell completion script, source it in your shell's configuration
+    Completions {
+        /// Shell to generate the completions for
+        shell: Shell,
+    },
+    /// Manage profiles, each with its own configuration, tokens and timers
+    Profile {
+        #[command(subcommand)]
+        command: ProfileSubcommand,
+    },
+    /// Show or submit your Tempo timesheet for approval
+    Timesheet {
+        #[command(subcommand)]
+        command: TimesheetSubcommand,
+    },
+    /// Reconstruct worklogs from your commits, edit them and submit them
+    Suggest {
+        /// First day to look at commits from (YYYY-MM-DD, today, yesterday or a weekday)
+        #[arg(long, value_parser = parsers::parse_date, default_value = "today")]
+        since: DateArg,
+        /// Submit the suggested worklogs without opening them in $EDITOR first
+        #[arg(long)]
+        no_edit: bool,
+    },
+    /// Use an issue for a branch, whatever its name is
+    Link {
+        /// Name of the branch, eg. `main`
+        branch: String,
+        /// Issue of the branch
+        #[arg(value_parser = parsers::parse_issue_id, add = ArgValueCandidates::new(issue_candidates))]
+        issue: JiraIssueKey,
+    },
+    /// Detect the issue of a branch from its name again
+    Unlink {
+        /// Name of the branch
+        branch: String,
+    },
+    /// Start and stop timers when checking out branches using a git hook
+    Hooks {
+        #[command(subcommand)]
+        command: HooksSubcommand,
+    },
+    /// Manage the cache of Jira issues and your user
+    Cache {
+        #[command(subcommand)]
+        command: CacheSubcommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HooksSubcommand {
+    /// Install the post-checkout hook in the current repository, an existing hook is still run
+    Install,
+    /// Remove the hook, restoring the previous one
+    Uninstall,
+    /// Run by the hook after a branch was checked out
+    #[command(hide = true)]
+    Checkout,
 }
 
-mod parsers {
+#[derive(Subcommand, Debug)]
+pub enum CacheSubcommand {
+    /// Forget everything cached, eg. after an issue was renamed
+    Clear,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+    Powershell,
+}
+
+impl Shell {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+            Self::Elvish => "elvish",
+            Self::Powershell => "powershell",
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileSubcommand {
+    /// List all profiles
+    List,
+    /// Create a new profile
+    Add {
+        /// Name of the profile
+        name: String,
+    },
+    /// Remove a profile with all its tokens and timers
+    Remove {
+        /// Name of the profile
+        name: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Use a profile by default
+    Default {
+        /// Name of the profile
+        name: String,
+    },
+}
+
+/// A timesheet period
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Period {
+    #[default]
+    Current,
+    Previous,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TimesheetSubcommand {
+    /// Show the approval status, reviewer and totals of a timesheet
+    Status {
+        #[arg(short, long, value_enum, default_value_t)]
+        period: Period,
+    },
+    /// Submit a timesheet for approval
+    Submit {
+        #[arg(short, long, value_enum, default_value_t)]
+        period: Period,
+        /// Reviewer to submit to (account ID, name or email), defaults to the current reviewer
+        #[arg(short, long)]
+        reviewer: Option<String>,
+        /// Comment for the reviewer
+        #[arg(short, long)]
+        comment: Option<String>,
+        /// Submit even if timers are still running
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+pub(crate) mod parsers {
+    use super::{DateArg, DurationArg, LogTime, RemainingArg};
     use crate::jira::{parse_issue_key, types::JiraIssueKey};
+    use chrono::NaiveTime;
+    use chrono::{NaiveDate, Weekday};
     use nom::{
-        bytes::complete::{tag, take_while},
-        combinator::map_res,
+        branch::alt,
+        bytes::complete::{tag, take_while_m_n},
+        character::complete::{char, digit1, space0},
+        combinator::{all_consuming, map_res, opt, recognize, verify},
+        sequence::{pair, preceded, terminated, tuple},
         IResult,
     };
 
@@ -76,57 +446,346 @@ mod parsers {
         }
     }
 
-    pub fn parse_arg(time: &str) -> Result<(usize, String), String> {
-        let original = time.to_owned();
-        match parse_duration(time) {
-            Ok(("", duration)) => Ok((duration, original)),
-            Ok((remaining, _)) => Err(format!(
-                "Could not parse this remaining duration fragment: {remaining}"
+    /// Parses a work attribute value given as `key=value`
+    pub fn parse_attribute(attribute: &str) -> Result<(String, String), String> {
+        match attribute.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err("expected KEY=VALUE".to_string()),
+        }
+    }
+
+    pub fn parse_date(date: &str) -> Result<DateArg, String> {
+        match date.to_lowercase().as_str() {
+            "today" => Ok(DateArg::Today),
+            "yesterday" => Ok(DateArg::Yesterday),
+            lowercase => lowercase
+                .parse::<Weekday>()
+                .map(DateArg::Weekday)
+                .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d").map(DateArg::Date))
+                .map_err(|_| {
+                    format!(
+                        "Invalid date, expected YYYY-MM-DD, today, yesterday or a weekday: {date}"
+                    )
+                }),
+        }
+    }
+
+    /// Parses either a range of time (`HH:MM-HH:MM`) or a duration
+    pub fn parse_log_time(time: &str) -> Result<LogTime, String> {
+        match time.split_once('-') {
+            Some((start, end)) => Ok(LogTime::Range(
+                parse_time_of_day(start.trim())?,
+                parse_time_of_day(end.trim())?,
             )),
-            Err(error) => Err(error.to_string()),
+            None => parse_arg(time).map(LogTime::Duration),
+        }
+    }
+
+    pub fn parse_remaining(remaining: &str) -> Result<RemainingArg, String> {
+        if remaining.trim().eq_ignore_ascii_case("auto") {
+            Ok(RemainingArg::Auto)
+        } else {
+            parse_arg(remaining).map(RemainingArg::Duration)
         }
     }
 
-    pub fn parse_duration(duration: &str) -> IResult<&str, usize> {
-        let (duration, hours) = parse_time(duration, "h", 60 * 60).unwrap_or((duration, 0));
-        let (duration, minutes) = parse_time(duration, "m", 60).unwrap_or((duration, 0));
-        let (duration, seconds) = parse_time(duration, "s", 1).unwrap_or((duration, 0));
+    pub fn parse_time_of_day(time: &str) -> Result<NaiveTime, String> {
+        NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .map_err(|_| format!("Invalid time of day, expected HH:MM[:SS]: {time}"))
+    }
+
+    /// Duration units, in the order they have to be written
+    const UNITS: [(&str, Unit); 5] = [
+        ("w", Unit::Workdays(5.0)),
+        ("d", Unit::Workdays(1.0)),
+        ("h", Unit::Seconds(60.0 * 60.0)),
+        ("m", Unit::Seconds(60.0)),
+        ("s", Unit::Seconds(1.0)),
+    ];
 
-        Ok((duration, hours + minutes + seconds))
+    #[derive(Clone, Copy)]
+    enum Unit {
+        Seconds(f64),
+        Workdays(f64),
     }
 
-    fn is_digit(c: char) -> bool {
-        c.is_ascii_digit()
+    pub fn parse_arg(time: &str) -> Result<DurationArg, String> {
+        let input = time.trim();
+        let result = parse_clock(input)
+            .or_else(|_| parse_minutes(input))
+            .or_else(|_| parse_duration(input));
+
+        match result {
+            Ok(("", (seconds, workdays))) => Ok(DurationArg {
+                seconds,
+                workdays,
+                text: time.trim().to_string(),
+            }),
+            Ok((remaining, _)) => Err(duration_error(time, remaining)),
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                Err(duration_error(time, error.input))
+            }
+            Err(nom::Err::Incomplete(_)) => Err(duration_error(time, "")),
+        }
+    }
+
+    /// Shows the input with a caret pointing to the start of the fragment that could not be parsed
+    fn duration_error(time: &str, remaining: &str) -> String {
+        let offset = time.trim_end().len() - remaining.len();
+        let fragment = remaining.split_whitespace().next().unwrap_or("");
+        let problem = if fragment.is_empty() {
+            "expected a duration".to_string()
+        } else {
+            format!("could not parse '{fragment}'")
+        };
+
+        format!(
+            "{problem} (eg. 1h30m, 1h 30m, 1.5h, 2d, 1:30 or 90)\n  {time}\n  {}^",
+            " ".repeat(offset)
+        )
+    }
+
+    /// Parses durations like `1w 2d 3h 4m 5s` or `1.5h`, returns the seconds and workdays
+    pub fn parse_duration(duration: &str) -> IResult<&str, (f64, f64)> {
+        let mut input = duration;
+        let (mut seconds, mut workdays) = (0.0, 0.0);
+        let mut parsed_any = false;
+
+        for (unit_tag, unit) in UNITS {
+            let Ok((remaining, amount)) = parse_time(input, unit_tag) else {
+                continue;
+            };
+            (input, _) = space0(remaining)?;
+            parsed_any = true;
+
+            match unit {
+                Unit::Seconds(multiplier) => seconds += amount * multiplier,
+                Unit::Workdays(multiplier) => workdays += amount * multiplier,
+            }
+        }
+
+        if !parsed_any {
+            // Reports the position of the error
+            parse_number(duration)?;
+        }
+
+        Ok((input, (seconds, workdays)))
+    }
+
+    /// Parses clock notation (`H:MM` or `H:MM:SS`)
+    fn parse_clock(time: &str) -> IResult<&str, (f64, f64)> {
+        let two_digits = || {
+            verify(
+                map_res(take_while_m_n(2, 2, is_digit), |n: &str| n.parse::<u32>()),
+                |n| *n < 60,
+            )
+        };
+        let (time, (hours, _, minutes, seconds)) = tuple((
+            map_res(digit1, |n: &str| n.parse::<u32>()),
+            char(':'),
+            two_digits(),
+            opt(preceded(char(':'), two_digits())),
+        ))(time)?;
+        let seconds = hours * 3600 + minutes * 60 + seconds.unwrap_or(0);
+
+        Ok((time, (seconds as f64, 0.0)))
+    }
+
+    /// A plain number is an amount of minutes
+    fn parse_minutes(time: &str) -> IResult<&str, (f64, f64)> {
+        let (time, minutes) = all_consuming(parse_number)(time)?;
+
+        Ok((time, (minutes * 60.0, 0.0)))
     }
 
-    // TODO: Get rid of repetition
+    fn parse_number(i: &str) -> IResult<&str, f64> {
+        map_res(
+            recognize(alt((
+                recognize(pair(digit1, opt(pair(char('.'), digit1)))),
+                recognize(pair(char('.'), digit1)),
+            ))),
+            |n: &str| n.parse::<f64>(),
+        )(i)
+    }
 
-    fn parse_time<'a>(
-        i: &'a str,
-        end_tag: &'static str,
-        multiplier: usize,
-    ) -> IResult<&'a str, usize> {
-        let mut duration = map_res(take_while(is_digit), |i: &str| i.parse::<usize>())(i)?;
-        (duration.0, _) = tag(end_tag)(duration.0)?;
+    fn is_digit(c: char) -> bool {
+        c.is_ascii_digit()
+    }
 
-        Ok((duration.0, duration.1 * multiplier))
+    fn parse_time<'a>(i: &'a str, end_tag: &'static str) -> IResult<&'a str, f64> {
+        terminated(parse_number, pair(space0, tag(end_tag)))(i)
     }
 
     #[cfg(test)]
     mod test {
-        use super::parse_arg;
+        use super::{
+            parse_arg, parse_attribute, parse_date, parse_log_time, parse_remaining,
+            parse_time_of_day,
+        };
+        use crate::args::{DateArg, LogTime, RemainingArg};
+        use chrono::{NaiveDate, NaiveTime, Weekday};
+
+        const WORKDAY: usize = 8 * 60 * 60;
+
+        fn seconds(time: &str) -> usize {
+            parse_arg(time).unwrap().seconds(WORKDAY)
+        }
 
         #[test]
         fn test_correct_times() {
-            assert_eq!(parse_arg("1m").unwrap().0, 60);
-            assert_eq!(parse_arg("6h7s").unwrap().0, 21607);
-            assert_eq!(parse_arg("1h30m").unwrap().0, 5400);
+            assert_eq!(seconds("1m"), 60);
+            assert_eq!(seconds("6h7s"), 21607);
+            assert_eq!(seconds("1h30m"), 5400);
+        }
+
+        #[test]
+        fn test_extended_times() {
+            let cases = [
+                ("1h", 3600),
+                ("45s", 45),
+                ("1d", WORKDAY),
+                ("1w", 5 * WORKDAY),
+                ("1w2d", 7 * WORKDAY),
+                ("1d 4h", WORKDAY + 4 * 3600),
+                ("6d3s", 6 * WORKDAY + 3),
+                ("1.5h", 5400),
+                ("0.25h", 900),
+                (".5h", 1800),
+                ("0.5d", WORKDAY / 2),
+                ("1h 30m", 5400),
+                ("1h  30m 15s", 5415),
+                ("2 h", 7200),
+                ("1:30", 5400),
+                ("0:45", 2700),
+                ("10:00:30", 36030),
+                ("90", 5400),
+                ("1.5", 90),
+                ("0", 0),
+                (" 1h ", 3600),
+            ];
+
+            for (time, expected) in cases {
+                assert_eq!(seconds(time), expected, "parsing {time:?}");
+            }
+        }
+
+        #[test]
+        fn test_custom_workday() {
+            let time = parse_arg("1d 1h").unwrap();
+
+            assert_eq!(time.seconds(6 * 60 * 60), 7 * 60 * 60);
+            assert_eq!(time.text, "1d 1h");
         }
 
         #[test]
         fn test_incorrect_times() {
             assert!(parse_arg("1s2h").is_err());
-            assert!(parse_arg("6d3s").is_err());
+            assert!(parse_arg("6x3s").is_err());
+
+            for time in [
+                "", "h", "1h30", "1h1h", "1:3", "1:60", "1.h", "1h 2x", "-1h",
+            ] {
+                assert!(parse_arg(time).is_err(), "parsing {time:?}");
+            }
+        }
+
+        #[test]
+        fn test_error_caret() {
+            let error = parse_arg("1h 3x").unwrap_err();
+            assert!(error.starts_with("could not parse '3x'"), "{error}");
+            assert!(error.ends_with("\n  1h 3x\n     ^"), "{error}");
+
+            let error = parse_arg("1s2h").unwrap_err();
+            assert!(error.ends_with("\n  1s2h\n    ^"), "{error}");
+
+            let error = parse_arg("h").unwrap_err();
+            assert!(error.ends_with("\n  h\n  ^"), "{error}");
+
+            let error = parse_arg("").unwrap_err();
+            assert!(error.starts_with("expected a duration"), "{error}");
+        }
+
+        #[test]
+        fn test_time_of_day() {
+            assert_eq!(
+                parse_time_of_day("09:30").unwrap(),
+                NaiveTime::from_hms_opt(9, 30, 0).unwrap()
+            );
+            assert_eq!(
+                parse_time_of_day("17:05:10").unwrap(),
+                NaiveTime::from_hms_opt(17, 5, 10).unwrap()
+            );
+            assert!(parse_time_of_day("25:00").is_err());
+        }
+
+        #[test]
+        fn test_attribute() {
+            assert_eq!(
+                parse_attribute("Billable = true").unwrap(),
+                ("Billable".to_string(), "true".to_string())
+            );
+            assert_eq!(
+                parse_attribute("_Note_=a=b").unwrap(),
+                ("_Note_".to_string(), "a=b".to_string())
+            );
+            assert!(parse_attribute("Billable").is_err());
+            assert!(parse_attribute("=true").is_err());
+        }
+
+        #[test]
+        fn test_log_time() {
+            let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+            assert_eq!(
+                parse_log_time("09:00-10:30").unwrap(),
+                LogTime::Range(time(9, 0), time(10, 30))
+            );
+            assert_eq!(
+                parse_log_time("13:15 - 14:00").unwrap(),
+                LogTime::Range(time(13, 15), time(14, 0))
+            );
+            assert_eq!(
+                parse_log_time("1:30").unwrap(),
+                LogTime::Duration(parse_arg("1:30").unwrap())
+            );
+            assert!(parse_log_time("09:00-").is_err());
+            assert!(parse_log_time("9-10").is_err());
+        }
+
+        #[test]
+        fn test_remaining() {
+            assert_eq!(parse_remaining("Auto").unwrap(), RemainingArg::Auto);
+            assert_eq!(
+                parse_remaining("2h").unwrap(),
+                RemainingArg::Duration(parse_arg("2h").unwrap())
+            );
+            assert!(parse_remaining("soon").is_err());
+        }
+
+        #[test]
+        fn test_dates() {
+            // A saturday
+            let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+            let date = |text| parse_date(text).unwrap().resolve(today);
+
+            assert_eq!(date("today"), today);
+            assert_eq!(date("Yesterday"), today.pred_opt().unwrap());
+            assert_eq!(
+                date("2026-10-16"),
+                NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+            );
+            assert_eq!(
+                date("monday"),
+                NaiveDate::from_ymd_opt(2026, 10, 12).unwrap()
+            );
+            assert_eq!(date("fri"), NaiveDate::from_ymd_opt(2026, 10, 16).unwrap());
+            assert_eq!(date("saturday"), today);
+            assert_eq!(parse_date("sun").unwrap(), DateArg::Weekday(Weekday::Sun));
+            assert!(parse_date("someday").is_err());
+            assert!(parse_date("2026-13-01").is_err());
         }
     }
 }
@@ -0,0 +1,159 @@
+use crate::{
+    error::TempomatError,
+    tempo::types::{AttributeValue, WorkAttribute, WorkAttributeType},
+};
+
+/// Matches work attribute values, by the key or name of the attribute, against the attributes
+/// configured in Tempo. A later value of the same attribute wins, then the account. Returns the
+/// values to submit, static list values are validated
+pub fn resolve(
+    attributes: &[WorkAttribute],
+    given: &[(String, String)],
+    account: Option<&str>,
+) -> Result<Vec<AttributeValue>, TempomatError> {
+    let mut values: Vec<AttributeValue> = Vec::new();
+    let mut set = |attribute: &WorkAttribute, value: String| {
+        values.retain(|value| value.key != attribute.key);
+        values.push(AttributeValue {
+            key: attribute.key.clone(),
+            value,
+            work_attribute_id: attribute.id,
+        });
+    };
+
+    for (key, value) in given {
+        let attribute = attributes
+            .iter()
+            .find(|attribute| attribute.key == *key || attribute.name.eq_ignore_ascii_case(key))
+            .ok_or_else(|| TempomatError::UnknownAttribute(key.clone()))?;
+
+        set(attribute, validate(attribute, value)?);
+    }
+
+    if let Some(account) = account {
+        let attribute = attributes
+            .iter()
+            .find(|attribute| attribute.kind == WorkAttributeType::Account)
+            .ok_or_else(|| TempomatError::UnknownAttribute("account".to_string()))?;
+
+        set(attribute, account.to_string());
+    }
+
+    if let Some(missing) = attributes.iter().find(|attribute| {
+        attribute.required && !values.iter().any(|value| value.key == attribute.key)
+    }) {
+        Err(TempomatError::MissingAttribute(missing.name.clone()))?
+    }
+
+    Ok(values)
+}
+
+/// Checks a value of an attribute, returns the value Tempo expects
+fn validate(attribute: &WorkAttribute, value: &str) -> Result<String, TempomatError> {
+    let invalid = |allowed: String| TempomatError::InvalidAttributeValue {
+        attribute: attribute.name.clone(),
+        value: value.to_string(),
+        allowed,
+    };
+
+    match attribute.kind {
+        // Static list values can also be given by their name
+        WorkAttributeType::StaticList => attribute
+            .values
+            .iter()
+            .find(|allowed| {
+                allowed.eq_ignore_ascii_case(value)
+                    || attribute
+                        .names
+                        .get(*allowed)
+                        .is_some_and(|name| name.eq_ignore_ascii_case(value))
+            })
+            .cloned()
+            .ok_or_else(|| invalid(attribute.values.join(", "))),
+        WorkAttributeType::Checkbox => match value.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok("true".to_string()),
+            "false" | "no" | "0" => Ok("false".to_string()),
+            _ => Err(invalid("true, false".to_string())),
+        },
+        WorkAttributeType::InputNumeric => value
+            .parse::<f64>()
+            .map(|_| value.to_string())
+            .map_err(|_| invalid("a number".to_string())),
+        _ => Ok(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::resolve;
+    use crate::tempo::types::{WorkAttribute, WorkAttributeType};
+
+    fn attribute(key: &str, name: &str, kind: WorkAttributeType, values: &[&str]) -> WorkAttribute {
+        WorkAttribute {
+            key: key.to_string(),
+            name: name.to_string(),
+            kind,
+            required: true,
+            values: values.iter().map(|value| value.to_string()).collect(),
+            names: values
+                .iter()
+                .map(|value| (value.to_string(), format!("Name of {value}")))
+                .collect(),
+            id: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_attributes() {
+        let attributes = [
+            attribute("_Account_", "Account", WorkAttributeType::Account, &[]),
+            attribute(
+                "_Phase_",
+                "Phase",
+                WorkAttributeType::StaticList,
+                &["dev", "ops"],
+            ),
+            attribute("_Billable_", "Billable", WorkAttributeType::Checkbox, &[]),
+        ];
+        let given = |values: &[(&str, &str)]| -> Vec<(String, String)> {
+            values
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+
+        // The later value wins, however the attribute is named
+        let values = resolve(
+            &attributes,
+            &given(&[
+                ("phase", "dev"),
+                ("_Billable_", "yes"),
+                ("Phase", "Name of ops"),
+                ("account", "ACC-0"),
+            ]),
+            Some("ACC-1"),
+        )
+        .unwrap();
+        let values: Vec<_> = values
+            .iter()
+            .map(|value| (value.key.as_str(), value.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("_Billable_", "true"),
+                ("_Phase_", "ops"),
+                ("_Account_", "ACC-1"),
+            ]
+        );
+
+        assert!(resolve(
+            &attributes,
+            &given(&[("Phase", "qa"), ("Billable", "no")]),
+            Some("ACC-1")
+        )
+        .is_err());
+        assert!(resolve(&attributes, &given(&[("Billable", "no")]), Some("ACC-1")).is_err());
+        assert!(resolve(&attributes, &given(&[("Colour", "red")]), None).is_err());
+    }
+}
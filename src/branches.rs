@@ -0,0 +1,243 @@
+use crate::{
+    config::{Config, Saveable},
+    error::TempomatError,
+    git::{Commit, Repository},
+    jira::types::JiraIssueKey,
+};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Name of the group of a branch pattern holding the issue key
+const ISSUE_GROUP: &str = "issue";
+
+/// Uppercase issue keys, found anywhere in a branch when no configured pattern matches
+const DEFAULT_PATTERN: &str = r"(?P<issue>\p{Lu}[\p{Lu}0-9_]*-[0-9]+)";
+
+/// Issues linked to branches with `tempomat link`, by the repository's directory and the branch
+/// name, so that eg. `main` can have another issue in every repository
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BranchLinks(pub BTreeMap<PathBuf, BTreeMap<String, String>>);
+
+impl Saveable for BranchLinks {
+    fn path(root: &Path) -> PathBuf {
+        root.join("links.ron")
+    }
+}
+
+/// Finds the issue key of a branch, from a link or the branch name
+#[derive(Debug)]
+pub struct BranchMatcher {
+    links: BTreeMap<String, String>,
+    patterns: Vec<Regex>,
+    default_pattern: Regex,
+    /// Projects keys are taken from, empty allows any
+    projects: Vec<String>,
+}
+
+impl BranchMatcher {
+    /// The rules of the repository checked out in `work_dir`, its own patterns are tried first
+    pub fn new(
+        config: Option<&Config>,
+        work_dir: Option<&Path>,
+        mut links: BranchLinks,
+    ) -> Result<Self, TempomatError> {
+        let repository = config
+            .zip(work_dir)
+            .and_then(|(config, work_dir)| config.repository(work_dir));
+
+        let patterns = repository
+            .into_iter()
+            .flat_map(|repository| &repository.branch_patterns)
+            .chain(
+                config
+                    .into_iter()
+                    .flat_map(|config| &config.branch_patterns),
+            )
+            .map(|pattern| compile(pattern))
+            .collect::<Result<_, _>>()?;
+
+        let projects = match repository {
+            Some(repository) if !repository.projects.is_empty() => repository.projects.clone(),
+            _ => config
+                .map(|config| config.projects.clone())
+                .unwrap_or_default(),
+        };
+
+        Ok(Self {
+            links: work_dir
+                .and_then(|work_dir| links.0.remove(work_dir))
+                .unwrap_or_default(),
+            patterns,
+            default_pattern: Regex::new(DEFAULT_PATTERN).expect("the default pattern is valid"),
+            projects: projects
+                .iter()
+                .map(|project| project.to_uppercase())
+                .collect(),
+        })
+    }
+
+    /// The rules of `repository`, with the links saved in `root`
+    pub fn read(
+        root: &Path,
+        config: Option<&Config>,
+        repository: &Repository,
+    ) -> Result<Self, TempomatError> {
+        let links = BranchLinks::try_read(root)?.unwrap_or_default();
+
+        Self::new(config, Some(&repository.work_dir), links)
+    }
+
+    /// Issue key of a branch, a linked issue wins over the patterns
+    pub fn issue_key(&self, branch: &str) -> Option<JiraIssueKey> {
+        if let Some(issue) = self.links.get(branch) {
+            return issue.parse().ok();
+        }
+
+        self.patterns
+            .iter()
+            .chain([&self.default_pattern])
+            .find_map(|pattern| self.find(pattern, branch))
+    }
+
+    /// Issue key of a commit, from its message or else the branch it was found from
+    pub fn commit_key(&self, commit: &Commit) -> Option<JiraIssueKey> {
+        let branch = commit
+            .branch
+            .strip_prefix("refs/heads/")
+            .unwrap_or(&commit.branch);
+
+        self.find(&self.default_pattern, &commit.message)
+            .or_else(|| self.issue_key(branch))
+    }
+
+    /// First key matched by `pattern` that is of an allowed project
+    fn find(&self, pattern: &Regex, text: &str) -> Option<JiraIssueKey> {
+        pattern
+            .captures_iter(text)
+            // The group may not take part in the match, eg. in an alternation
+            .filter_map(|captures| {
+                let issue = captures.name(ISSUE_GROUP)?;
+                issue.as_str().to_uppercase().parse().ok()
+            })
+            .find(|key: &JiraIssueKey| {
+                self.projects.is_empty() || self.projects.contains(&key.board)
+            })
+    }
+}
+
+/// Issue key of the branch checked out in the repository containing `repo`
+pub fn current_branch_key(
+    root: &Path,
+    config: Option<&Config>,
+    repo: &Path,
+) -> Result<Option<JiraIssueKey>, TempomatError> {
+    let repository = Repository::discover(repo)?;
+    let matcher = BranchMatcher::read(root, config, &repository)?;
+
+    Ok(repository
+        .current_branch()?
+        .and_then(|branch| matcher.issue_key(&branch)))
+}
+
+/// Issue key of the branch that was checked out before the current one
+pub fn previous_branch_key(
+    root: &Path,
+    config: Option<&Config>,
+    repo: &Path,
+) -> Result<Option<JiraIssueKey>, TempomatError> {
+    let repository = Repository::discover(repo)?;
+    let matcher = BranchMatcher::read(root, config, &repository)?;
+
+    Ok(repository
+        .previous_branch()?
+        .and_then(|branch| matcher.issue_key(&branch)))
+}
+
+fn compile(pattern: &str) -> Result<Regex, TempomatError> {
+    let invalid = |reason: String| TempomatError::InvalidBranchPattern(pattern.to_string(), reason);
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|error| invalid(error.to_string()))?;
+
+    if !regex.capture_names().any(|name| name == Some(ISSUE_GROUP)) {
+        Err(invalid("missing the `issue` group".to_string()))?
+    }
+
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BranchLinks, BranchMatcher};
+    use crate::config::{Config, RepositoryConfig};
+    use std::{env, path::Path};
+
+    fn key(matcher: &BranchMatcher, branch: &str) -> Option<String> {
+        matcher.issue_key(branch).map(|key| key.to_string())
+    }
+
+    #[test]
+    fn test_branch_patterns() {
+        let work_dir = env::temp_dir().canonicalize().unwrap();
+        let config = Config {
+            branch_patterns: vec![r"^feature/(?P<issue>[a-z][a-z0-9]*-\d+)".to_string()],
+            projects: vec!["dv".to_string(), "A1B".to_string()],
+            repositories: [(
+                work_dir.clone(),
+                RepositoryConfig {
+                    branch_patterns: vec![r"^(?P<issue>[a-z]+-\d+)$".to_string()],
+                    projects: vec!["OPS".to_string()],
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let links = || {
+            BranchLinks(
+                [(
+                    work_dir.clone(),
+                    [("main".to_string(), "OPS-1".to_string())].into(),
+                )]
+                .into(),
+            )
+        };
+        let matcher = BranchMatcher::new(Some(&config), None, links()).unwrap();
+
+        assert_eq!(
+            key(&matcher, "feature/dv-3124-foo").as_deref(),
+            Some("DV-3124")
+        );
+        assert_eq!(key(&matcher, "Feature/a1b-12").as_deref(), Some("A1B-12"));
+        assert_eq!(key(&matcher, "fix/UTF-8-in-DV-7").as_deref(), Some("DV-7"));
+        assert_eq!(key(&matcher, "fix/SHA-256"), None);
+        // Links only apply in their repository
+        assert_eq!(key(&matcher, "main"), None);
+
+        // The repository's projects replace the global ones
+        let matcher = BranchMatcher::new(Some(&config), Some(&work_dir), links()).unwrap();
+        assert_eq!(key(&matcher, "ops-5").as_deref(), Some("OPS-5"));
+        assert_eq!(key(&matcher, "feature/dv-1"), None);
+        assert_eq!(key(&matcher, "main").as_deref(), Some("OPS-1"));
+
+        let alternation = Config {
+            branch_patterns: vec![r"^(?:(?P<issue>[a-z]+-\d+)|main)".to_string()],
+            ..Default::default()
+        };
+        let matcher = BranchMatcher::new(Some(&alternation), None, Default::default()).unwrap();
+        assert_eq!(key(&matcher, "main"), None);
+        assert_eq!(key(&matcher, "ops-2").as_deref(), Some("OPS-2"));
+
+        let invalid = Config {
+            branch_patterns: vec![r"feature/(?P<key>.*)".to_string()],
+            ..Default::default()
+        };
+        assert!(
+            BranchMatcher::new(Some(&invalid), Some(Path::new("/")), Default::default()).is_err()
+        );
+    }
+}
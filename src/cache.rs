@@ -0,0 +1,417 @@
+use crate::{
+    config::Saveable,
+    error::TempomatError,
+    jira::{
+        api::JiraApi,
+        types::{Issue, IssueFields, JiraIssueKey, Myself},
+    },
+    tempo::{api::TempoApi, types::WorkAttribute},
+};
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// A value fetched from Jira and when it was fetched
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched: NaiveDateTime,
+}
+
+impl<T: Clone> Cached<T> {
+    fn fresh(&self, now: NaiveDateTime, ttl: Duration) -> Option<T> {
+        (now - self.fetched < ttl).then(|| self.value.clone())
+    }
+}
+
+/// The parts of an issue needed to submit worklogs, they rarely change
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedIssue {
+    pub id: String,
+    pub summary: String,
+}
+
+impl CachedIssue {
+    fn issue(self, key: String) -> Issue {
+        Issue {
+            id: self.id,
+            key,
+            fields: IssueFields {
+                summary: self.summary,
+                timetracking: Default::default(),
+            },
+        }
+    }
+}
+
+/// Jira lookups done for every worklog, so that they don't have to be fetched again
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cache {
+    /// Jira the lookups were done on, the cache is discarded when it changes
+    #[serde(default)]
+    pub jira_url: String,
+    /// The account the lookups were done with, empty for personal access tokens
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub me: Option<Cached<Myself>>,
+    /// Issues by their key
+    #[serde(default)]
+    pub issues: HashMap<String, Cached<CachedIssue>>,
+    /// Work attributes configured in Tempo
+    #[serde(default)]
+    pub work_attributes: Option<Cached<Vec<WorkAttribute>>>,
+}
+
+impl Cache {
+    /// Reads the cache, unless it was filled for another Jira or account
+    pub fn read(root: &Path, jira_url: &str, email: &str) -> Result<Self, TempomatError> {
+        let cache = Self::try_read(root)?
+            .filter(|cache| cache.jira_url == jira_url && cache.email == email);
+
+        Ok(cache.unwrap_or_else(|| Self {
+            jira_url: jira_url.to_string(),
+            email: email.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    /// Gets the issue, the current user and, given `tempo_api`, the work attributes. The ones that
+    /// are not cached or older than `ttl` are fetched concurrently. `fresh_issue` always fetches
+    /// the issue, eg. to get its current estimate
+    pub async fn worklog_lookups(
+        &mut self,
+        jira_api: &JiraApi<'_>,
+        tempo_api: Option<&TempoApi<'_>>,
+        key: &JiraIssueKey,
+        fresh_issue: bool,
+        now: NaiveDateTime,
+        ttl: Duration,
+    ) -> Result<(Issue, Myself, Option<Vec<WorkAttribute>>), TempomatError> {
+        let cached_issue = self
+            .issues
+            .get(&key.to_string())
+            .filter(|_| !fresh_issue)
+            .and_then(|issue| issue.fresh(now, ttl));
+        let cached_me = self.me.as_ref().and_then(|me| me.fresh(now, ttl));
+        let cached_attributes = self
+            .work_attributes
+            .as_ref()
+            .and_then(|attributes| attributes.fresh(now, ttl));
+        let (issue_cached, me_cached, attributes_cached) = (
+            cached_issue.is_some(),
+            cached_me.is_some(),
+            cached_attributes.is_some(),
+        );
+
+        let (issue, me, attributes) = tokio::join!(
+            async {
+                match cached_issue {
+                    Some(issue) => Ok(issue.issue(key.to_string())),
+                    None => {
+                        debug!("Fetching issue {key}");
+                        jira_api.get_issue(key).await
+                    }
+                }
+            },
+            async {
+                match cached_me {
+                    Some(me) => Ok(me),
+                    None => {
+                        debug!("Fetching the current user");
+                        jira_api.get_me().await
+                    }
+                }
+            },
+            async {
+                match (tempo_api, cached_attributes) {
+                    (None, _) => Ok(None),
+                    (Some(_), Some(attributes)) => Ok(Some(attributes)),
+                    (Some(tempo_api), None) => {
+                        debug!("Fetching the work attributes");
+                        tempo_api.get_work_attributes().await.map(Some)
+                    }
+                }
+            }
+        );
+        let (issue, me, attributes) = (issue?, me?, attributes?);
+
+        self.forget_expired(now, ttl);
+        if !issue_cached {
+            self.insert_issue(&issue, now);
+        }
+        if !me_cached {
+            self.me = Some(Cached {
+                value: me.clone(),
+                fetched: now,
+            });
+        }
+        if let Some(attributes) = attributes.as_ref().filter(|_| !attributes_cached) {
+            self.work_attributes = Some(Cached {
+                value: attributes.clone(),
+                fetched: now,
+            });
+        }
+
+        Ok((issue, me, attributes))
+    }
+
+    /// Gets the current user, fetching it if it is not cached or older than `ttl`
+    pub async fn me(
+        &mut self,
+        jira_api: &JiraApi<'_>,
+        now: NaiveDateTime,
+        ttl: Duration,
+    ) -> Result<Myself, TempomatError> {
+        if let Some(me) = self.me.as_ref().and_then(|me| me.fresh(now, ttl)) {
+            return Ok(me);
+        }
+
+        debug!("Fetching the current user");
+        let me = jira_api.get_me().await?;
+        self.me = Some(Cached {
+            value: me.clone(),
+            fetched: now,
+        });
+
+        Ok(me)
+    }
+
+    /// Gets issues by their numeric ID, as returned by the Tempo API. The ones that are not cached
+    /// or older than `ttl` are fetched with a single search
+    pub async fn issues_by_id(
+        &mut self,
+        jira_api: &JiraApi<'_>,
+        ids: impl IntoIterator<Item = usize>,
+        now: NaiveDateTime,
+        ttl: Duration,
+    ) -> Result<HashMap<usize, Issue>, TempomatError> {
+        let mut issues = HashMap::new();
+        let mut missing = Vec::new();
+        for id in ids {
+            if issues.contains_key(&id) || missing.contains(&id) {
+                continue;
+            }
+            let cached = self.issues.iter().find_map(|(key, issue)| {
+                (issue.value.id == id.to_string())
+                    .then(|| issue.fresh(now, ttl))
+                    .flatten()
+                    .map(|issue| issue.issue(key.clone()))
+            });
+            match cached {
+                Some(issue) => {
+                    issues.insert(id, issue);
+                }
+                None => missing.push(id),
+            }
+        }
+
+        self.forget_expired(now, ttl);
+        // A search returns at most 100 issues
+        for chunk in missing.chunks(100) {
+            let ids: Vec<_> = chunk.iter().map(usize::to_string).collect();
+            debug!("Fetching issues {}", ids.join(", "));
+            for issue in jira_api
+                .search(&format!("id in ({})", ids.join(",")))
+                .await?
+            {
+                let id = issue
+                    .id
+                    .parse()
+                    .map_err(|_| TempomatError::InvalidIssueId(issue.id.clone()))?;
+                self.insert_issue(&issue, now);
+                issues.insert(id, issue);
+            }
+        }
+
+        // The search leaves out issues that don't exist or can't be seen
+        if let Some(id) = missing.iter().find(|id| !issues.contains_key(id)) {
+            Err(TempomatError::IssueNotFound(id.to_string()))?
+        }
+
+        Ok(issues)
+    }
+
+    /// Forgets expired issues, so that the cache doesn't grow forever
+    fn forget_expired(&mut self, now: NaiveDateTime, ttl: Duration) {
+        self.issues.retain(|_, issue| now - issue.fetched < ttl);
+    }
+
+    fn insert_issue(&mut self, issue: &Issue, now: NaiveDateTime) {
+        self.issues.insert(
+            issue.key.clone(),
+            Cached {
+                value: CachedIssue {
+                    id: issue.id.clone(),
+                    summary: issue.fields.summary.clone(),
+                },
+                fetched: now,
+            },
+        );
+    }
+
+    /// Removes the cache, returns true if there was one
+    pub fn clear(root: &Path) -> Result<bool, TempomatError> {
+        match fs::remove_file(Self::path(root)) {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(error) => Err(error)?,
+        }
+    }
+}
+
+impl Saveable for Cache {
+    fn path(root: &Path) -> PathBuf {
+        root.join("cache.ron")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cache, Cached, CachedIssue};
+    use crate::{
+        config::{Config, Deployment, Saveable},
+        jira::{api::JiraApi, types::Myself, AtlassianTokens},
+        tempo::{
+            api::TempoApi,
+            types::{WorkAttribute, WorkAttributeType},
+        },
+    };
+    use chrono::{Duration, NaiveDate};
+    use std::{env, fs};
+
+    #[tokio::test]
+    async fn test_cached_lookups() {
+        let now = NaiveDate::from_ymd_opt(2023, 4, 10)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        // Nothing listens here, so every lookup that is not cached fails
+        let config = Config {
+            jira_url: Some("http://127.0.0.1:9".to_string()),
+            deployment: Deployment::Server,
+            ..Default::default()
+        };
+        let tokens = AtlassianTokens {
+            token: "jira".to_string(),
+            email: "me@example.com".to_string(),
+        };
+        let jira_api = JiraApi(&tokens, &config);
+        let tempo_api = TempoApi("tempo", &config);
+        let mut cache = Cache {
+            jira_url: config.jira_url().unwrap(),
+            email: tokens.email.clone(),
+            me: Some(Cached {
+                value: Myself {
+                    account_id: "me".to_string(),
+                    display_name: "Me".to_string(),
+                },
+                fetched: now - Duration::hours(2),
+            }),
+            issues: [(
+                "DV-1".to_string(),
+                Cached {
+                    value: CachedIssue {
+                        id: "10000".to_string(),
+                        summary: "Review".to_string(),
+                    },
+                    fetched: now - Duration::hours(2),
+                },
+            )]
+            .into(),
+            work_attributes: Some(Cached {
+                value: vec![WorkAttribute {
+                    key: "_Account_".to_string(),
+                    name: "Account".to_string(),
+                    kind: WorkAttributeType::Account,
+                    required: false,
+                    values: Vec::new(),
+                    names: Default::default(),
+                    id: Some(1),
+                }],
+                fetched: now - Duration::hours(2),
+            }),
+        };
+        let key = "DV-1".parse().unwrap();
+        let day = Duration::hours(24);
+
+        let (issue, me, attributes) = cache
+            .worklog_lookups(&jira_api, None, &key, false, now, day)
+            .await
+            .unwrap();
+        assert_eq!(issue.id, "10000");
+        assert_eq!(issue.fields.summary, "Review");
+        assert_eq!(me.account_id, "me");
+        assert!(attributes.is_none());
+
+        let (_, _, attributes) = cache
+            .worklog_lookups(&jira_api, Some(&tempo_api), &key, false, now, day)
+            .await
+            .unwrap();
+        assert_eq!(attributes.unwrap()[0].key, "_Account_");
+
+        assert_eq!(
+            cache.me(&jira_api, now, day).await.unwrap().account_id,
+            "me"
+        );
+        let issues = cache
+            .issues_by_id(&jira_api, [10000, 10000], now, day)
+            .await
+            .unwrap();
+        assert_eq!(issues[&10000].key, "DV-1");
+        assert!(cache
+            .issues_by_id(&jira_api, [10000, 10001], now, day)
+            .await
+            .is_err());
+
+        assert!(cache
+            .worklog_lookups(&jira_api, None, &key, true, now, day)
+            .await
+            .is_err());
+        assert!(cache
+            .worklog_lookups(&jira_api, None, &key, false, now, Duration::hours(1))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_cache_of_other_account() {
+        let root = env::temp_dir().join(format!("tempomat-cache-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let cache = Cache {
+            jira_url: "https://jira.example.com".to_string(),
+            email: "me@example.com".to_string(),
+            me: Some(Cached {
+                value: Myself {
+                    account_id: "me".to_string(),
+                    display_name: "Me".to_string(),
+                },
+                fetched: NaiveDate::from_ymd_opt(2023, 4, 10)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap(),
+            }),
+            issues: Default::default(),
+            work_attributes: None,
+        };
+        cache.save(&root).unwrap();
+
+        let read = |jira_url, email| Cache::read(&root, jira_url, email).unwrap();
+        assert!(read("https://jira.example.com", "me@example.com")
+            .me
+            .is_some());
+        assert!(read("https://jira.example.com", "other@example.com")
+            .me
+            .is_none());
+        let other = read("https://other.atlassian.net", "me@example.com");
+        assert!(other.me.is_none());
+        assert_eq!(other.jira_url, "https://other.atlassian.net");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    args::PROFILE_ENV,
+    branches,
+    config::{Config, Saveable},
+    config_root,
+    journal::Journal,
+    profiles::{self, Profiles},
+    timers::TempoTimers,
+};
+use clap_complete::engine::CompletionCandidate;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable used by the shell to request completions
+pub const COMPLETE_ENV: &str = "COMPLETE";
+
+/// Suggests issue keys from the current branch, running timers and recently logged worklogs
+pub fn issue_candidates() -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<(String, &'static str)> = Vec::new();
+
+    if let Some(root) = completion_root() {
+        let config = Config::try_read(&root).ok().flatten();
+        if let Ok(Some(key)) = branches::current_branch_key(&root, config.as_ref(), Path::new("."))
+        {
+            candidates.push((key.to_string(), "current branch"));
+        }
+
+        if let Ok(Some(timers)) = TempoTimers::try_read(&root) {
+            let mut issues: Vec<_> = timers.0.into_keys().collect();
+            issues.sort();
+            candidates.extend(issues.into_iter().map(|issue| (issue, "running timer")));
+        }
+
+        if let Ok(Some(journal)) = Journal::try_read(&root) {
+            candidates.extend(
+                journal
+                    .0
+                    .into_iter()
+                    .rev()
+                    .map(|entry| (entry.issue, "recently logged")),
+            );
+        }
+    }
+
+    let mut seen = Vec::new();
+    candidates
+        .into_iter()
+        .filter(|(issue, _)| {
+            let new = !seen.contains(issue);
+            seen.push(issue.clone());
+            new
+        })
+        .map(|(issue, help)| CompletionCandidate::new(issue).help(Some(help.into())))
+        .collect()
+}
+
+/// The arguments are not parsed while completing, so the profile can only come from the environment
+fn completion_root() -> Option<PathBuf> {
+    let root = config_root(None).ok()?;
+    let profile = env::var(PROFILE_ENV).ok().unwrap_or_else(|| {
+        Profiles::try_read(&root)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .default_profile()
+            .to_string()
+    });
+
+    profiles::profile_root(&root, &profile).ok()
+}
@@ -1,12 +1,16 @@
 use crate::{
     error::TempomatError,
-    jira::{self, AtlassianTokens},
+    jira::{self, types::JiraIssueKey, AtlassianTokens},
     tempo::oauth::{actions as tempo_actions, TempoAccessTokens},
 };
 use chrono::{Duration, NaiveDateTime, Utc};
+use clap::ValueEnum;
 use colored::Colorize;
+use directories::BaseDirs;
+use ron::{extensions::Extensions, Options};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
 };
@@ -14,10 +18,242 @@ use tracing::debug;
 
 const AUTH_FILENAME: &str = "auth.ron";
 const CONFIG_FILENAME: &str = "config.ron";
+const LOCAL_CONFIG_FILENAME: &str = ".tempomat.ron";
+const TEMPO_CLOUD_URL: &str = "https://api.tempo.io";
+const DEFAULT_WORKDAY_HOURS: f64 = 8.0;
+const DEFAULT_CACHE_HOURS: f64 = 24.0;
+const DEFAULT_ISSUE_JQL: &str =
+    "assignee = currentUser() AND statusCategory != Done ORDER BY updated DESC";
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Config {
-    pub atlassian_instance: String,
+    /// Name of the Atlassian Cloud instance, shorthand for `https://<instance>.atlassian.net`
+    #[serde(default)]
+    pub atlassian_instance: Option<String>,
+    /// Base URL of Jira, overrides `atlassian_instance`
+    #[serde(default)]
+    pub jira_url: Option<String>,
+    /// Base URL of the Tempo API, defaults to the Tempo Cloud API or Jira for self-hosted instances
+    #[serde(default)]
+    pub tempo_url: Option<String>,
+    #[serde(default)]
+    pub deployment: Deployment,
+    /// Length of a workday in hours, used for durations in days and weeks (defaults to 8)
+    #[serde(default)]
+    pub workday_hours: Option<f64>,
+    /// Default work attribute values of every worklog, eg. `{"Phase": "Development"}`
+    #[serde(default)]
+    pub default_attributes: BTreeMap<String, String>,
+    /// Default work attribute values by Jira project, eg. `{"DV": {"Account": "ACC-1"}}`
+    #[serde(default)]
+    pub project_attributes: HashMap<String, BTreeMap<String, String>>,
+    /// `default_attributes` of the repository's `.tempomat.ron`, they win over the global ones
+    #[serde(skip)]
+    pub local_default_attributes: BTreeMap<String, String>,
+    /// `project_attributes` of the repository's `.tempomat.ron`
+    #[serde(skip)]
+    pub local_project_attributes: HashMap<String, BTreeMap<String, String>>,
+    /// Issue used when none is given and the branch has no issue key
+    #[serde(default)]
+    pub default_issue: Option<String>,
+    /// Description of worklogs logged without one, `{issue}` and `{project}` are replaced with
+    /// the issue key and its project
+    #[serde(default)]
+    pub description: Option<String>,
+    /// JQL of the issues to pick from when the issue cannot be detected, defaults to the
+    /// unresolved issues assigned to you
+    #[serde(default)]
+    pub issue_jql: Option<String>,
+    /// How long issues and the current user are cached in hours, 0 disables the cache
+    /// (defaults to 24)
+    #[serde(default)]
+    pub cache_hours: Option<f64>,
+    /// What the git hook does with the timer of the previous branch on checkout
+    #[serde(default)]
+    pub checkout_action: CheckoutAction,
+    /// Repositories whose commits `suggest` looks at, defaults to the current one
+    #[serde(default)]
+    pub suggest_repos: Vec<PathBuf>,
+    /// Regexes finding the issue key in a branch name with an `issue` group, they are matched
+    /// case-insensitively before the default of an uppercase key, eg. `"^feature/(?P<issue>[a-z]+-[0-9]+)"`
+    #[serde(default)]
+    pub branch_patterns: Vec<String>,
+    /// Jira projects whose keys are taken from branches, so that eg. `UTF-8` isn't. Empty allows any
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Settings of single repositories by their path
+    #[serde(default)]
+    pub repositories: HashMap<PathBuf, RepositoryConfig>,
+}
+
+/// Settings from a `.tempomat.ron` of a repository, or one of the directories containing it. The
+/// Jira and Tempo URLs can't be changed, so that a cloned repository can't get your tokens
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct LocalConfig {
+    /// Profile used unless one is given with `--profile`
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub default_issue: Option<String>,
+    #[serde(default)]
+    pub default_attributes: BTreeMap<String, String>,
+    #[serde(default)]
+    pub project_attributes: HashMap<String, BTreeMap<String, String>>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub issue_jql: Option<String>,
+    #[serde(default)]
+    pub branch_patterns: Vec<String>,
+    #[serde(default)]
+    pub projects: Vec<String>,
+}
+
+impl LocalConfig {
+    /// Reads the closest `.tempomat.ron` in `dir` or its parents
+    pub fn find(dir: &Path) -> Result<Option<Self>, TempomatError> {
+        for dir in dir.ancestors() {
+            let path = dir.join(LOCAL_CONFIG_FILENAME);
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            debug!("Using {}", path.display());
+
+            return Ok(Some(ron_options().from_str(&text)?));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Settings that only apply to one repository
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RepositoryConfig {
+    /// Tried before the global `branch_patterns`
+    #[serde(default)]
+    pub branch_patterns: Vec<String>,
+    /// Replaces the global `projects` if not empty
+    #[serde(default)]
+    pub projects: Vec<String>,
+}
+
+/// What happens to the running timer of a branch when another branch is checked out
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum CheckoutAction {
+    #[default]
+    Pause,
+    /// Stop and submit the timer
+    Stop,
+    /// Ask what to do with the timer and whether to start one for the new branch
+    Prompt,
+}
+
+/// Where Jira and Tempo are hosted
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Deployment {
+    /// Atlassian Cloud with the Tempo Cloud API
+    #[default]
+    Cloud,
+    /// Self-hosted Jira Server or Data Center with Tempo Timesheets
+    Server,
+}
+
+impl Config {
+    /// Seconds in a workday
+    pub fn workday_seconds(&self) -> usize {
+        (self.workday_hours.unwrap_or(DEFAULT_WORKDAY_HOURS) * 60.0 * 60.0).round() as usize
+    }
+
+    /// How long Jira lookups are cached
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::seconds((self.cache_hours.unwrap_or(DEFAULT_CACHE_HOURS) * 60.0 * 60.0) as i64)
+    }
+
+    /// JQL of the issues to pick from
+    pub fn issue_jql(&self) -> &str {
+        self.issue_jql.as_deref().unwrap_or(DEFAULT_ISSUE_JQL)
+    }
+
+    /// Work attribute values of a worklog for an issue of `project`, `given` overrides the defaults
+    pub fn worklog_attributes(
+        &self,
+        project: &str,
+        given: Vec<(String, String)>,
+    ) -> Vec<(String, String)> {
+        let project_attributes = self.project_attributes.get(project).into_iter().flatten();
+        let local_project_attributes = self
+            .local_project_attributes
+            .get(project)
+            .into_iter()
+            .flatten();
+
+        // Applied in this order, so later values win once they are matched to Tempo's attributes
+        self.default_attributes
+            .iter()
+            .chain(project_attributes)
+            .chain(&self.local_default_attributes)
+            .chain(local_project_attributes)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .chain(given)
+            .collect()
+    }
+
+    /// The given description, or else the description template filled in for `issue`
+    pub fn describe(&self, issue: &JiraIssueKey, given: Option<String>) -> Option<String> {
+        given.or_else(|| {
+            let template = self.description.as_ref()?;
+            Some(
+                template
+                    .replace("{issue}", &issue.to_string())
+                    .replace("{project}", &issue.board),
+            )
+        })
+    }
+
+    /// Applies the settings of a repository's `.tempomat.ron`
+    pub fn merge(&mut self, local: LocalConfig) {
+        // Kept apart, so that they win over the global project attributes
+        self.local_default_attributes = local.default_attributes;
+        self.local_project_attributes = local.project_attributes;
+        self.default_issue = local.default_issue.or(self.default_issue.take());
+        self.description = local.description.or(self.description.take());
+        self.issue_jql = local.issue_jql.or(self.issue_jql.take());
+        // The repository's patterns are tried first
+        self.branch_patterns = [
+            local.branch_patterns,
+            std::mem::take(&mut self.branch_patterns),
+        ]
+        .concat();
+        if !local.projects.is_empty() {
+            self.projects = local.projects;
+        }
+    }
+
+    /// Settings of the repository checked out in `work_dir`
+    pub fn repository(&self, work_dir: &Path) -> Option<&RepositoryConfig> {
+        self.repositories.iter().find_map(|(path, repository)| {
+            let path = fs::canonicalize(expand_home(path)).ok()?;
+            (path == work_dir).then_some(repository)
+        })
+    }
+
+    /// Base URL of Jira, without a trailing slash
+    pub fn jira_url(&self) -> Result<String, TempomatError> {
+        match (&self.jira_url, &self.atlassian_instance) {
+            (Some(url), _) => Ok(url.trim_end_matches('/').to_string()),
+            (None, Some(instance)) => Ok(format!("https://{instance}.atlassian.net")),
+            (None, None) => Err(TempomatError::MissingJiraUrl),
+        }
+    }
+
+    /// Base URL of the Tempo API, without a trailing slash
+    pub fn tempo_url(&self) -> Result<String, TempomatError> {
+        match (&self.tempo_url, self.deployment) {
+            (Some(url), _) => Ok(url.trim_end_matches('/').to_string()),
+            (None, Deployment::Cloud) => Ok(TEMPO_CLOUD_URL.to_string()),
+            (None, Deployment::Server) => self.jira_url(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -28,35 +264,70 @@ pub struct TempoAccessMetadata {
     pub tokens: TempoAccessTokens,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct APITokens {
     /// Jira only has one access token
     pub jira: AtlassianTokens,
     /// OAuth tokens for tempo
-    pub tempo: TempoAccessMetadata,
+    #[serde(default)]
+    pub tempo: Option<TempoAccessMetadata>,
+    /// Tempo API token, used instead of OAuth if set
+    #[serde(default)]
+    pub tempo_token: Option<String>,
 }
 
 impl APITokens {
-    pub async fn initialize(config: &Config) -> Result<Self, TempomatError> {
+    /// Gets new tokens, uses `tempo_token` instead of going through the Tempo OAuth flow if set
+    pub async fn initialize(
+        config: &Config,
+        tempo_token: Option<String>,
+    ) -> Result<Self, TempomatError> {
         // Not using Result::ok() here since we want the process to fail if something went wrong
-        println!("Getting Tempo tokens...");
-        let tempo = tempo_actions::login(config).await?.into();
-        println!("Getting Jira tokens...");
-        let jira = jira::get_token()?;
+        let tempo = if tempo_token.is_none() && config.deployment == Deployment::Cloud {
+            eprintln!("Getting Tempo tokens...");
+            Some(tempo_actions::login(config).await?.into())
+        } else {
+            None
+        };
+        eprintln!("Getting Jira tokens...");
+        let jira = jira::get_token(config)?;
+        // Tempo Timesheets on Jira Server is authenticated like Jira itself
+        let tempo_token = match config.deployment {
+            Deployment::Cloud => tempo_token,
+            Deployment::Server => tempo_token.or_else(|| Some(jira.token.clone())),
+        };
 
-        println!("{}", "Successfully got access tokens!".green());
+        eprintln!("{}", "Successfully got access tokens!".green());
 
-        Ok(Self { tempo, jira })
+        Ok(Self {
+            tempo,
+            tempo_token,
+            jira,
+        })
+    }
+
+    /// The token used to authenticate to the Tempo API
+    pub fn tempo_access_token(&self) -> Result<&str, TempomatError> {
+        match (&self.tempo_token, &self.tempo) {
+            (Some(token), _) => Ok(token),
+            (None, Some(tempo)) => Ok(&tempo.tokens.access_token),
+            (None, None) => Err(TempomatError::MissingTempoAccess),
+        }
     }
 
     /// Refreshes tokens if necesarry, returns true if the token was refreshed
     pub async fn refresh_tokens(&mut self) -> Result<bool, TempomatError> {
-        if (Utc::now().naive_utc() - self.tempo.last_refresh)
-            > Duration::seconds(self.tempo.tokens.expires_in as i64)
+        let Some(tempo) = self.tempo.as_mut().filter(|_| self.tempo_token.is_none()) else {
+            debug!("Using a Tempo API token, nothing to refresh");
+            return Ok(false);
+        };
+
+        if (Utc::now().naive_utc() - tempo.last_refresh)
+            > Duration::seconds(tempo.tokens.expires_in as i64)
         {
             debug!("Token expired, getting new tokens...");
-            let tokens = tempo_actions::refresh_token(&self.tempo.tokens).await?;
-            self.tempo = tokens.into();
+            let tokens = tempo_actions::refresh_token(&tempo.tokens).await?;
+            *tempo = tokens.into();
             Ok(true)
         } else {
             debug!("Tokens not expired, not doing anything");
@@ -77,21 +348,37 @@ impl Saveable for Config {
     }
 }
 
+/// Replaces a leading `~` with the home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), BaseDirs::new()) {
+        (Ok(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Optional values are written without `Some(...)`, so that fields can be made optional without
+/// breaking existing files
+pub(crate) fn ron_options() -> Options {
+    Options::default().with_default_extension(Extensions::IMPLICIT_SOME)
+}
+
 pub trait Saveable: Serialize + DeserializeOwned {
     fn path(root: &Path) -> PathBuf;
 
     fn save(&self, root: &Path) -> Result<(), TempomatError> {
         let path = Self::path(root);
 
-        fs::write(path, ron::to_string(self)?)?;
+        fs::write(path, ron_options().to_string(self)?)?;
 
         Ok(())
     }
 
     fn try_read(root: &Path) -> Result<Option<Self>, TempomatError> {
         let path = Self::path(root);
-        let Ok(config) = fs::read_to_string(path) else { return Ok(None) };
-        let config = ron::from_str(&config)?;
+        let Ok(config) = fs::read_to_string(path) else {
+            return Ok(None);
+        };
+        let config = ron_options().from_str(&config)?;
 
         Ok(Some(config))
     }
@@ -105,3 +392,121 @@ impl From<TempoAccessTokens> for TempoAccessMetadata {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ron_options, APITokens, Config, Deployment, LocalConfig};
+
+    #[test]
+    fn test_read_oauth_tokens() {
+        let tokens: APITokens = ron_options()
+            .from_str(
+                r#"(jira:(token:"jira",email:"me@example.com"),tempo:(last_refresh:"2023-04-10T10:00:00",tokens:(access_token:"access",expires_in:3600,token_type:"Bearer",scope:"",refresh_token:"refresh")))"#,
+            )
+            .unwrap();
+
+        assert!(tokens.tempo_token.is_none());
+        assert_eq!(tokens.tempo_access_token().unwrap(), "access");
+    }
+
+    #[test]
+    fn test_tempo_token_preferred() {
+        let mut tokens: APITokens = ron_options()
+            .from_str(r#"(jira:(token:"jira",email:"me@example.com"),tempo_token:"token")"#)
+            .unwrap();
+
+        assert!(tokens.tempo.is_none());
+        assert_eq!(tokens.tempo_access_token().unwrap(), "token");
+
+        tokens.tempo_token = None;
+        assert!(tokens.tempo_access_token().is_err());
+    }
+
+    #[test]
+    fn test_base_urls() {
+        let mut config: Config = ron_options()
+            .from_str(r#"(atlassian_instance:"example")"#)
+            .unwrap();
+
+        assert_eq!(config.deployment, Deployment::Cloud);
+        assert_eq!(config.jira_url().unwrap(), "https://example.atlassian.net");
+        assert_eq!(config.tempo_url().unwrap(), "https://api.tempo.io");
+
+        config.jira_url = Some("https://jira.example.com/".to_string());
+        config.deployment = Deployment::Server;
+        assert_eq!(config.jira_url().unwrap(), "https://jira.example.com");
+        assert_eq!(config.tempo_url().unwrap(), "https://jira.example.com");
+
+        config.tempo_url = Some("http://localhost:8080".to_string());
+        assert_eq!(config.tempo_url().unwrap(), "http://localhost:8080");
+    }
+
+    /// The value of an attribute that is submitted, the last one
+    fn value<'a>(attributes: &'a [(String, String)], key: &str) -> &'a str {
+        attributes
+            .iter()
+            .rev()
+            .find(|(attribute, _)| attribute == key)
+            .map(|(_, value)| value.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_project_attributes() {
+        let config: Config = ron_options()
+            .from_str(r#"(project_attributes:{"DV":{"Account":"ACC-1","Billable":"true"}})"#)
+            .unwrap();
+
+        let attributes =
+            config.worklog_attributes("DV", vec![("billable".to_string(), "false".to_string())]);
+        assert_eq!(value(&attributes, "Account"), "ACC-1");
+        // The given value comes last, whatever its case
+        assert_eq!(attributes.last().unwrap().1, "false");
+        assert!(config.worklog_attributes("OPS", Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_merge_local_config() {
+        let mut config: Config = ron_options()
+            .from_str(
+                r#"(jira_url:"https://jira.example.com",default_attributes:{"Phase":"Dev"},project_attributes:{"DV":{"Account":"ACC-1"}},branch_patterns:["(?P<issue>g-[0-9]+)"],projects:["DV"])"#,
+            )
+            .unwrap();
+        let local: LocalConfig = ron_options()
+            .from_str(
+                r#"(profile:"client",default_issue:"DV-1",default_attributes:{"Account":"ACC-2"},description:"Development of {issue} ({project})",branch_patterns:["(?P<issue>l-[0-9]+)"])"#,
+            )
+            .unwrap();
+        assert_eq!(local.profile.as_deref(), Some("client"));
+
+        config.merge(local);
+        assert_eq!(config.jira_url().unwrap(), "https://jira.example.com");
+        assert_eq!(config.default_issue.as_deref(), Some("DV-1"));
+        assert_eq!(
+            config.branch_patterns,
+            ["(?P<issue>l-[0-9]+)", "(?P<issue>g-[0-9]+)"]
+        );
+        assert_eq!(config.projects, ["DV"]);
+
+        // The repository's attributes win over all global ones
+        let attributes = config.worklog_attributes("OPS", Vec::new());
+        assert_eq!(value(&attributes, "Phase"), "Dev");
+        assert_eq!(value(&attributes, "Account"), "ACC-2");
+        assert_eq!(
+            value(&config.worklog_attributes("DV", Vec::new()), "Account"),
+            "ACC-2"
+        );
+
+        let issue = "DV-7".parse().unwrap();
+        assert_eq!(
+            config.describe(&issue, None).as_deref(),
+            Some("Development of DV-7 (DV)")
+        );
+        assert_eq!(
+            config
+                .describe(&issue, Some("Review".to_string()))
+                .as_deref(),
+            Some("Review")
+        );
+    }
+}
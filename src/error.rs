@@ -6,7 +6,7 @@ pub enum TempomatError {
     #[error("HTTP error: {0:?}")]
     ReqwestErrror(#[from] reqwest::Error),
     #[error("Failed to revoke OAuth refresh token: {0:?}")]
-    OAuthRevokeFailed(reqwest::Response),
+    OAuthRevokeFailed(Box<reqwest::Response>),
     #[error("Failed to join task (this should never happen, please report): {0:?}")]
     JoinError(#[from] tokio::task::JoinError),
     #[error("I/O error: {0:?}")]
@@ -21,6 +21,8 @@ pub enum TempomatError {
     RonError(#[from] ron::Error),
     #[error("Failed to parse RON file: {0:?}")]
     RonParseError(#[from] ron::error::SpannedError),
+    #[error("Failed to serialize JSON: {0:?}")]
+    JsonError(#[from] serde_json::Error),
     #[error("Missing configuration or tokens")]
     MissingConfigurations,
     #[error("Could not get Jira issue ID, use -i <issue_key> to set it")]
@@ -31,6 +33,122 @@ pub enum TempomatError {
     TimerInvalid,
     #[error("Tried to log negative time, did you change your timezone?")]
     NegativeTime,
+    #[error("The start of the date range is after its end")]
+    InvalidDateRange,
+    #[error("Jira returned an invalid issue ID: {0}")]
+    InvalidIssueId(String),
+    #[error("Invalid profile name '{0}', only letters, digits, '-' and '_' are allowed")]
+    InvalidProfileName(String),
+    #[error("Profile '{0}' does not exist, create it using 'tempomat profile add'")]
+    ProfileNotFound(String),
+    #[error("Profile '{0}' already exists")]
+    ProfileExists(String),
+    #[error("Missing Jira URL, log in again with --atlassian-instance or --jira-url")]
+    MissingJiraUrl,
+    #[error("Invalid Jira issue key: {0}")]
+    InvalidIssueKey(String),
+    #[error("No timer is running")]
+    NoRunningTimer,
+    #[error("More than one timer is running, use --from <issue_key> to choose one")]
+    AmbiguousTimer,
+    #[error("{0} does not exist in your timezone")]
+    NonexistentLocalTime(chrono::NaiveDateTime),
+    #[error("{0} happens twice in your timezone, because of a daylight saving time change")]
+    AmbiguousLocalTime(chrono::NaiveDateTime),
+    #[error("Worklogs can't end in the future")]
+    WorklogInFuture,
+    #[error("The end of the time range must be after its start")]
+    InvalidTimeRange,
+    #[error("A range of time already has a start, don't use --at with it")]
+    StartWithTimeRange,
+    #[error("There is no queued worklog at position {0}, see 'tempomat sync --list'")]
+    InvalidQueuePosition(usize),
+    #[error("{0} timer(s) still running, stop them or use --force to submit anyway")]
+    TimersRunning(usize),
+    #[error("The timesheet has no reviewer, choose one using --reviewer")]
+    MissingReviewer,
+    #[error("No user found matching '{0}'")]
+    UserNotFound(String),
+    #[error("More than one user matches '{0}', be more specific")]
+    AmbiguousUser(String),
+    #[error("There is no work attribute '{0}' in Tempo")]
+    UnknownAttribute(String),
+    #[error("Invalid value '{value}' for work attribute '{attribute}', use one of: {allowed}")]
+    InvalidAttributeValue {
+        attribute: String,
+        value: String,
+        allowed: String,
+    },
+    #[error("The work attribute '{0}' is required, set it using --attr or in config.ron")]
+    MissingAttribute(String),
+    #[error("Not inside a git repository")]
+    NoRepository,
+    #[error("Can't keep the existing hook, {0:?} is in the way")]
+    HookConflict(std::path::PathBuf),
+    #[error(
+        "Invalid worklog on line {0}, expected ISSUE YYYY-MM-DD HH:MM DURATION [DESCRIPTION]: {1}"
+    )]
+    InvalidDraftLine(usize, String),
+    #[error("The editor '{0}' failed, nothing was submitted")]
+    EditorFailed(String),
+    #[error("Invalid branch pattern {0:?}, expected a regex with an `issue` group: {1}")]
+    InvalidBranchPattern(String, String),
+    #[error("Tempo rejected the change to worklog {0}, check logs")]
+    WorklogRejected(usize),
+    #[error("Jira issue {0} does not exist or is not visible to you")]
+    IssueNotFound(String),
+}
+
+impl TempomatError {
+    /// Stable identifier of the error, for scripts using `--output json`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ReqwestErrror(..) => "http",
+            Self::OAuthRevokeFailed(..) => "oauth_revoke_failed",
+            Self::JoinError(..) => "join",
+            Self::IOError(..) => "io",
+            Self::MissingTempoAccess => "missing_tempo_access",
+            Self::MissingJiraAccess => "missing_jira_access",
+            Self::NoProjectDirs => "no_project_dirs",
+            Self::RonError(..) => "ron",
+            Self::RonParseError(..) => "ron_parse",
+            Self::JsonError(..) => "json",
+            Self::MissingConfigurations => "missing_configurations",
+            Self::CouldNotGetJiraIssueKey => "could_not_get_jira_issue_key",
+            Self::InvalidUtf(..) => "invalid_utf8",
+            Self::TimerInvalid => "timer_invalid",
+            Self::NegativeTime => "negative_time",
+            Self::InvalidDateRange => "invalid_date_range",
+            Self::InvalidIssueId(..) => "invalid_issue_id",
+            Self::InvalidProfileName(..) => "invalid_profile_name",
+            Self::ProfileNotFound(..) => "profile_not_found",
+            Self::ProfileExists(..) => "profile_exists",
+            Self::MissingJiraUrl => "missing_jira_url",
+            Self::InvalidIssueKey(..) => "invalid_issue_key",
+            Self::NoRunningTimer => "no_running_timer",
+            Self::AmbiguousTimer => "ambiguous_timer",
+            Self::NonexistentLocalTime(..) => "nonexistent_local_time",
+            Self::AmbiguousLocalTime(..) => "ambiguous_local_time",
+            Self::WorklogInFuture => "worklog_in_future",
+            Self::InvalidTimeRange => "invalid_time_range",
+            Self::StartWithTimeRange => "start_with_time_range",
+            Self::InvalidQueuePosition(..) => "invalid_queue_position",
+            Self::TimersRunning(..) => "timers_running",
+            Self::MissingReviewer => "missing_reviewer",
+            Self::UserNotFound(..) => "user_not_found",
+            Self::AmbiguousUser(..) => "ambiguous_user",
+            Self::UnknownAttribute(..) => "unknown_attribute",
+            Self::InvalidAttributeValue { .. } => "invalid_attribute_value",
+            Self::MissingAttribute(..) => "missing_attribute",
+            Self::NoRepository => "no_repository",
+            Self::HookConflict(..) => "hook_conflict",
+            Self::InvalidDraftLine(..) => "invalid_draft_line",
+            Self::EditorFailed(..) => "editor_failed",
+            Self::InvalidBranchPattern(..) => "invalid_branch_pattern",
+            Self::WorklogRejected(..) => "worklog_rejected",
+            Self::IssueNotFound(..) => "issue_not_found",
+        }
+    }
 }
 
 impl std::fmt::Debug for TempomatError {
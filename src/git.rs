@@ -1,14 +1,377 @@
-use crate::{
-    error::TempomatError,
-    jira::{self, types::JiraIssueKey},
+use crate::{config::expand_home, error::TempomatError};
+use chrono::NaiveDateTime;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
 };
-use std::process::Command;
 
-pub fn get_current_branch_key() -> Result<Option<JiraIssueKey>, TempomatError> {
-    let output = Command::new("git")
-        .args(["branch", "--show-current"])
-        .output()?;
-    let text = String::from_utf8(output.stdout)?;
+/// Separates the fields of a commit in the output of `git log`
+const FIELD_SEPARATOR: char = '\u{1f}';
 
-    Ok(jira::parse_issue_key_fuzzy(&text))
+/// A commit, as needed to reconstruct worklogs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    /// Commit time in the local timezone
+    pub time: NaiveDateTime,
+    /// The ref the commit was found from, eg. `refs/heads/feat/DV-1`
+    pub branch: String,
+    /// First line of the commit message
+    pub message: String,
+}
+
+/// A repository found on disk, read without running git
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repository {
+    /// The directory containing the checked out files
+    pub work_dir: PathBuf,
+    /// The `.git` directory, or for a linked worktree its directory in the main repository
+    pub git_dir: PathBuf,
+    /// The directory shared by all worktrees, with the refs, objects and hooks
+    pub common_dir: PathBuf,
+}
+
+impl Repository {
+    /// Finds the repository containing `path`, looking in its parents
+    pub fn discover(path: &Path) -> Result<Self, TempomatError> {
+        let path = fs::canonicalize(path).map_err(|_| TempomatError::NoRepository)?;
+
+        for dir in path.ancestors() {
+            let dot_git = dir.join(".git");
+            let git_dir = if dot_git.is_dir() {
+                dot_git
+            } else if dot_git.is_file() {
+                // Linked worktrees and submodules point to their git directory
+                let text = fs::read_to_string(&dot_git)?;
+                let Some(git_dir) = text.trim().strip_prefix("gitdir:") else {
+                    continue;
+                };
+                dir.join(git_dir.trim())
+            } else {
+                continue;
+            };
+
+            let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+                Ok(common_dir) => git_dir.join(common_dir.trim()),
+                Err(_) => git_dir.clone(),
+            };
+
+            return Ok(Self {
+                work_dir: dir.to_path_buf(),
+                git_dir,
+                common_dir,
+            });
+        }
+
+        Err(TempomatError::NoRepository)
+    }
+
+    /// Name of the checked out branch, also while it is being rebased. None if HEAD is detached
+    pub fn current_branch(&self) -> Result<Option<String>, TempomatError> {
+        let head = fs::read_to_string(self.git_dir.join("HEAD"))?;
+        if let Some(branch) = head.trim().strip_prefix("ref: refs/heads/") {
+            return Ok(Some(branch.to_string()));
+        }
+
+        // A rebase detaches HEAD, the branch being rebased is kept next to it
+        for rebase in ["rebase-merge", "rebase-apply"] {
+            if let Ok(name) = fs::read_to_string(self.git_dir.join(rebase).join("head-name")) {
+                let name = name.trim();
+                return Ok(Some(
+                    name.strip_prefix("refs/heads/").unwrap_or(name).to_string(),
+                ));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Branch (or commit) checked out before the current one, like `@{-1}`
+    pub fn previous_branch(&self) -> Result<Option<String>, TempomatError> {
+        let reflog = match fs::read_to_string(self.git_dir.join("logs").join("HEAD")) {
+            Ok(reflog) => reflog,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => Err(error)?,
+        };
+
+        Ok(parse_previous_branch(&reflog))
+    }
+
+    /// Directory of the hooks, respects `core.hooksPath`
+    pub fn hooks_dir(&self) -> Result<PathBuf, TempomatError> {
+        Ok(match self.config("core.hooksPath")? {
+            // Relative to the working tree, where hooks run
+            Some(path) => self.work_dir.join(expand_home(Path::new(&path))),
+            None => self.common_dir.join("hooks"),
+        })
+    }
+
+    /// A value of the git configuration like `core.hooksPath`, the repository's own configuration
+    /// wins over the global one. Includes and the system configuration are not read
+    pub fn config(&self, key: &str) -> Result<Option<String>, TempomatError> {
+        let xdg_config = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map_or_else(|| expand_home(Path::new("~/.config")), PathBuf::from);
+        let files = [
+            xdg_config.join("git/config"),
+            expand_home(Path::new("~/.gitconfig")),
+            self.common_dir.join("config"),
+        ];
+
+        let mut value = None;
+        for file in files {
+            match fs::read_to_string(file) {
+                Ok(text) => value = parse_config_value(&text, key).or(value),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => Err(error)?,
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+/// Directory of the hooks of the repository containing `repo`
+pub fn hooks_dir(repo: &Path) -> Result<PathBuf, TempomatError> {
+    Repository::discover(repo)?.hooks_dir()
+}
+
+/// The last value of `key` (`section.name`, both case-insensitive) in a git config file,
+/// subsections like `[remote "origin"]` are not supported
+fn parse_config_value(text: &str, key: &str) -> Option<String> {
+    let (section, name) = key.split_once('.')?;
+    let mut in_section = false;
+    let mut value = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or_default();
+            in_section = header.trim().eq_ignore_ascii_case(section);
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+
+        // A key without a value is true
+        let (line_key, line_value) = line.split_once('=').unwrap_or((line, "true"));
+        if line_key.trim().eq_ignore_ascii_case(name) {
+            value = Some(parse_config_string(line_value));
+        }
+    }
+
+    value
+}
+
+/// A value in a git config file, without its quotes, escapes and comment
+fn parse_config_string(raw: &str) -> String {
+    let mut value = String::new();
+    let mut quoted = false;
+    // Whitespace at the end is dropped, unless it was quoted
+    let mut end = 0;
+    let mut chars = raw.trim().chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => quoted = !quoted,
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(escaped) => value.push(escaped),
+                None => {}
+            },
+            '#' | ';' if !quoted => break,
+            char => value.push(char),
+        }
+        if quoted || !value.ends_with(char::is_whitespace) {
+            end = value.len();
+        }
+    }
+    value.truncate(end);
+
+    value
+}
+
+/// The branch moved away from by the last checkout in a HEAD reflog
+fn parse_previous_branch(reflog: &str) -> Option<String> {
+    reflog.lines().rev().find_map(|line| {
+        let (_, message) = line.split_once('\t')?;
+        let moves = message.strip_prefix("checkout: moving from ")?;
+        let (from, _) = moves.rsplit_once(" to ")?;
+
+        Some(from.to_string())
+    })
+}
+
+/// Commits of the configured git user in `repo` since `since` on any branch, newest first. Unlike
+/// everything else here this runs `git log`, so git has to be installed
+pub fn commits_since(repo: &Path, since: NaiveDateTime) -> Result<Vec<Commit>, TempomatError> {
+    let repository = Repository::discover(repo)?;
+    let email = repository.config("user.email")?.unwrap_or_default();
+
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(&repository.work_dir)
+        .args([
+            "log",
+            "--all",
+            "--source",
+            "--date=format-local:%Y-%m-%dT%H:%M:%S",
+            "--format=%cd%x1f%S%x1f%s",
+        ])
+        .arg(format!("--since={}", since.format("%Y-%m-%d %H:%M:%S")));
+    if !email.trim().is_empty() {
+        command.arg(format!("--author={}", email.trim()));
+    }
+
+    let output = command.output()?;
+    if !output.status.success() {
+        Err(TempomatError::NoRepository)?
+    }
+
+    Ok(parse_log(&String::from_utf8(output.stdout)?))
+}
+
+fn parse_log(log: &str) -> Vec<Commit> {
+    log.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, FIELD_SEPARATOR);
+            let time = fields.next()?.parse().ok()?;
+
+            Some(Commit {
+                time,
+                branch: fields.next()?.to_string(),
+                message: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_config_value, parse_log, parse_previous_branch, Commit, Repository};
+    use chrono::NaiveDate;
+    use std::{env, fs};
+
+    #[test]
+    fn test_discover_worktree() {
+        let root = env::temp_dir().join(format!("tempomat-git-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let git_dir = root.join("main/.git");
+        let worktree_git_dir = git_dir.join("worktrees/other");
+        fs::create_dir_all(&worktree_git_dir).unwrap();
+        fs::create_dir_all(root.join("other/src")).unwrap();
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/feat/DV-1-login\n").unwrap();
+        fs::write(
+            worktree_git_dir.join("HEAD"),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+        )
+        .unwrap();
+        fs::write(worktree_git_dir.join("commondir"), "../..\n").unwrap();
+        fs::write(
+            root.join("other/.git"),
+            format!("gitdir: {}\n", worktree_git_dir.display()),
+        )
+        .unwrap();
+
+        let main = Repository::discover(&root.join("main")).unwrap();
+        assert_eq!(
+            main.current_branch().unwrap().as_deref(),
+            Some("feat/DV-1-login")
+        );
+
+        let other = Repository::discover(&root.join("other/src")).unwrap();
+        assert_eq!(other.common_dir, worktree_git_dir.join("../.."));
+        assert_eq!(other.current_branch().unwrap(), None);
+
+        // Rebasing detaches HEAD
+        fs::create_dir_all(worktree_git_dir.join("rebase-merge")).unwrap();
+        fs::write(
+            worktree_git_dir.join("rebase-merge/head-name"),
+            "refs/heads/DV-2\n",
+        )
+        .unwrap();
+        assert_eq!(other.current_branch().unwrap().as_deref(), Some("DV-2"));
+
+        // The hooks are shared by all worktrees
+        fs::write(git_dir.join("config"), "[core]\n\thooksPath = .githooks\n").unwrap();
+        assert_eq!(other.hooks_dir().unwrap(), root.join("other/.githooks"));
+
+        assert!(Repository::discover(&root).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_previous_branch() {
+        let reflog = "0000 1111 A <a@b> 1681117200 +0200\tcommit (initial): init\n\
+                      1111 1111 A <a@b> 1681117260 +0200\tcheckout: moving from main to feat/DV-1\n\
+                      1111 2222 A <a@b> 1681117320 +0200\tcommit: Fix login\n\
+                      2222 1111 A <a@b> 1681117380 +0200\tcheckout: moving from feat/DV-1 to main\n";
+
+        assert_eq!(parse_previous_branch(reflog).as_deref(), Some("feat/DV-1"));
+        assert_eq!(parse_previous_branch(""), None);
+    }
+
+    #[test]
+    fn test_parse_config_value() {
+        let config = "[core]\n\
+                      \tbare = false\n\
+                      [remote \"origin\"]\n\
+                      \turl = git@example.com:repo.git\n\
+                      [User]\n\
+                      \temail = me@example.com ; work\n\
+                      \tname = \"Me \\\"Myself\\\"\" # comment\n\
+                      [core]\n\
+                      \thooksPath = first\n\
+                      \tHOOKSPATH = \"hooks; here \"\n\
+                      \tfilemode\n";
+
+        assert_eq!(
+            parse_config_value(config, "user.email").as_deref(),
+            Some("me@example.com")
+        );
+        assert_eq!(
+            parse_config_value(config, "user.name").as_deref(),
+            Some("Me \"Myself\"")
+        );
+        assert_eq!(
+            parse_config_value(config, "core.hooksPath").as_deref(),
+            Some("hooks; here ")
+        );
+        assert_eq!(
+            parse_config_value(config, "core.filemode").as_deref(),
+            Some("true")
+        );
+        assert_eq!(parse_config_value(config, "remote.url"), None);
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let commits = parse_log(
+            "2023-04-10T11:30:00\u{1f}refs/heads/feat/DV-1\u{1f}Fix login\n2023-04-10T09:00:00\u{1f}refs/heads/main\u{1f}Merge DV-2: a\u{1f}b\n",
+        );
+
+        assert_eq!(
+            commits,
+            [
+                Commit {
+                    time: NaiveDate::from_ymd_opt(2023, 4, 10)
+                        .unwrap()
+                        .and_hms_opt(11, 30, 0)
+                        .unwrap(),
+                    branch: "refs/heads/feat/DV-1".to_string(),
+                    message: "Fix login".to_string(),
+                },
+                Commit {
+                    time: NaiveDate::from_ymd_opt(2023, 4, 10)
+                        .unwrap()
+                        .and_hms_opt(9, 0, 0)
+                        .unwrap(),
+                    branch: "refs/heads/main".to_string(),
+                    message: "Merge DV-2: a\u{1f}b".to_string(),
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,123 @@
+use crate::error::TempomatError;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The hook git runs after a branch is checked out
+pub const HOOK_NAME: &str = "post-checkout";
+
+/// Identifies hooks written by tempomat
+const MARKER: &str = "# Installed by tempomat";
+
+/// What was done by `install`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Installed {
+    New,
+    /// An existing hook was moved aside and is run before tempomat's
+    Chained,
+    /// tempomat's hook was already there, it was rewritten
+    Updated,
+}
+
+/// The hook script, `command` is run on every branch checkout
+fn script(command: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+{MARKER}, remove it using 'tempomat hooks uninstall'
+chained="$(dirname "$0")/{HOOK_NAME}.chained"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+
+# The third argument is 0 when only files were checked out
+if [ "$3" = 1 ]; then
+    # Hooks get no input, prompts need the terminal
+    (exec </dev/tty) 2>/dev/null && exec </dev/tty
+    {command} hooks checkout || true
+fi
+"#
+    )
+}
+
+fn chained_path(hooks: &Path) -> PathBuf {
+    hooks.join(format!("{HOOK_NAME}.chained"))
+}
+
+fn is_ours(hook: &Path) -> bool {
+    fs::read_to_string(hook).is_ok_and(|script| script.contains(MARKER))
+}
+
+/// Writes the hook into the `hooks` directory, keeping any existing hook
+pub fn install(hooks: &Path, command: &str) -> Result<Installed, TempomatError> {
+    let hook = hooks.join(HOOK_NAME);
+    let installed = if is_ours(&hook) {
+        Installed::Updated
+    } else if hook.exists() {
+        let chained = chained_path(hooks);
+        if chained.exists() {
+            Err(TempomatError::HookConflict(chained.clone()))?
+        }
+
+        fs::rename(&hook, chained)?;
+        Installed::Chained
+    } else {
+        fs::create_dir_all(hooks)?;
+        Installed::New
+    };
+
+    fs::write(&hook, script(command))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(installed)
+}
+
+/// Removes the hook and restores the hook it was chained to, returns false if it wasn't installed
+pub fn uninstall(hooks: &Path) -> Result<bool, TempomatError> {
+    let hook = hooks.join(HOOK_NAME);
+    if !is_ours(&hook) {
+        return Ok(false);
+    }
+
+    fs::remove_file(&hook)?;
+    let chained = chained_path(hooks);
+    if chained.exists() {
+        fs::rename(chained, hook)?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{install, uninstall, Installed, HOOK_NAME};
+    use std::{env, fs};
+
+    #[test]
+    fn test_install_chained() {
+        let hooks = env::temp_dir().join(format!("tempomat-hooks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&hooks);
+        fs::create_dir_all(&hooks).unwrap();
+        let hook = hooks.join(HOOK_NAME);
+        fs::write(&hook, "#!/bin/sh\necho existing\n").unwrap();
+
+        assert_eq!(install(&hooks, "tempomat").unwrap(), Installed::Chained);
+        assert_eq!(install(&hooks, "tempomat").unwrap(), Installed::Updated);
+        let script = fs::read_to_string(&hook).unwrap();
+        assert!(script.contains("tempomat hooks checkout"), "{script}");
+        assert!(hooks.join("post-checkout.chained").exists());
+
+        assert!(uninstall(&hooks).unwrap());
+        assert_eq!(
+            fs::read_to_string(&hook).unwrap(),
+            "#!/bin/sh\necho existing\n"
+        );
+        assert!(!uninstall(&hooks).unwrap());
+
+        fs::remove_dir_all(&hooks).unwrap();
+    }
+}
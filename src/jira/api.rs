@@ -1,24 +1,30 @@
-use reqwest::Client;
+use reqwest::{Method, RequestBuilder};
 
 use super::{
-    types::{Issue, JiraIssueKey, Myself},
+    types::{Issue, JiraIssueKey, Myself, SearchResults, User},
     AtlassianTokens,
 };
-use crate::{config::Config, error::TempomatError};
+use crate::{
+    config::{Config, Deployment},
+    error::TempomatError,
+    http_client,
+};
 
 pub struct JiraApi<'a>(pub &'a AtlassianTokens, pub &'a Config);
 
 impl<'a> JiraApi<'a> {
     pub async fn get_issue(&self, key: &JiraIssueKey) -> Result<Issue, TempomatError> {
-        let client = Client::new();
-        let response: Issue = client
-            .get(format!(
-                "https://{}.atlassian.net/rest/api/3/issue/{}",
-                self.1.atlassian_instance,
-                key.to_string()
-            ))
-            .header("Accept", "application/json")
-            .basic_auth(&self.0.email, Some(&self.0.token))
+        self.fetch_issue(&key.to_string()).await
+    }
+
+    /// Gets an issue by its numeric ID, as returned by the Tempo API
+    pub async fn get_issue_by_id(&self, id: usize) -> Result<Issue, TempomatError> {
+        self.fetch_issue(&id.to_string()).await
+    }
+
+    async fn fetch_issue(&self, id_or_key: &str) -> Result<Issue, TempomatError> {
+        let response: Issue = self
+            .request(Method::GET, &format!("issue/{id_or_key}"))?
             .send()
             .await?
             .json()
@@ -28,19 +34,85 @@ impl<'a> JiraApi<'a> {
     }
 
     pub async fn get_me(&self) -> Result<Myself, TempomatError> {
-        let client = Client::new();
-        let response: Myself = client
-            .get(format!(
-                "https://{}.atlassian.net/rest/api/3/myself",
-                self.1.atlassian_instance,
-            ))
-            .header("Accept", "application/json")
-            .basic_auth(&self.0.email, Some(&self.0.token))
+        let response: Myself = self
+            .request(Method::GET, "myself")?
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn get_user(&self, account_id: &str) -> Result<User, TempomatError> {
+        let query = match self.1.deployment {
+            Deployment::Cloud => "accountId",
+            Deployment::Server => "key",
+        };
+        let response: User = self
+            .request(Method::GET, "user")?
+            .query(&[(query, account_id)])
             .send()
             .await?
+            .error_for_status()?
             .json()
             .await?;
 
         Ok(response)
     }
+
+    /// Searches users by their name, email address or username
+    pub async fn search_users(&self, query: &str) -> Result<Vec<User>, TempomatError> {
+        let parameter = match self.1.deployment {
+            Deployment::Cloud => "query",
+            Deployment::Server => "username",
+        };
+        let response: Vec<User> = self
+            .request(Method::GET, "user/search")?
+            .query(&[(parameter, query)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Searches issues using JQL, returns the first page of results
+    pub async fn search(&self, jql: &str) -> Result<Vec<Issue>, TempomatError> {
+        let response: SearchResults = self
+            .request(Method::GET, "search")?
+            .query(&[
+                ("jql", jql),
+                ("fields", "summary,timetracking"),
+                ("maxResults", "100"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.issues)
+    }
+
+    /// Creates an authenticated request to the REST API, Jira Server only supports version 2
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder, TempomatError> {
+        let version = match self.1.deployment {
+            Deployment::Cloud => 3,
+            Deployment::Server => 2,
+        };
+        let request = http_client()
+            .request(
+                method,
+                format!("{}/rest/api/{version}/{path}", self.1.jira_url()?),
+            )
+            .header("Accept", "application/json");
+
+        Ok(match self.1.deployment {
+            Deployment::Cloud => request.basic_auth(&self.0.email, Some(&self.0.token)),
+            Deployment::Server => request.bearer_auth(&self.0.token),
+        })
+    }
 }
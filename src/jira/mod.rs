@@ -2,53 +2,62 @@ pub mod api;
 pub mod types;
 
 use nom::{
-    bytes::complete::{tag, take_till, take_while1},
-    combinator::{map_res, opt},
-    sequence::tuple,
+    bytes::complete::{tag, take_till, take_while, take_while1},
+    character::complete::satisfy,
+    combinator::{map_res, opt, recognize},
+    sequence::{pair, tuple},
     IResult,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{error::TempomatError, jira::types::JiraIssueKey};
+use crate::{
+    config::{Config, Deployment},
+    error::TempomatError,
+    jira::types::JiraIssueKey,
+    prompt::prompt,
+};
 use std::io::{self, Write};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AtlassianTokens {
     pub token: String,
     pub email: String,
 }
 
 /// Retreives the token from CLI
-pub fn get_token() -> Result<AtlassianTokens, TempomatError> {
-    fn prompt(prompt: &str) -> Result<String, TempomatError> {
-        let mut response = String::new();
-
-        {
-            let mut stdout = io::stdout().lock();
-            let _ = stdout.write(prompt.as_bytes());
-            let _ = stdout.flush();
-        }
-
-        io::stdin().read_line(&mut response)?;
-        Ok(response.trim().to_string())
-    }
+pub fn get_token(config: &Config) -> Result<AtlassianTokens, TempomatError> {
     const ATLASSIAN_LINK: &str = "https://id.atlassian.com/manage-profile/security/api-tokens";
 
-    println!("Go to {} and generate a new access token", ATLASSIAN_LINK);
+    let link = match config.deployment {
+        Deployment::Cloud => ATLASSIAN_LINK.to_string(),
+        Deployment::Server => format!(
+            "{}/secure/ViewProfile.jspa?selectedTab=com.atlassian.pats.pats-plugin:jira-user-personal-access-tokens",
+            config.jira_url()?
+        ),
+    };
+
+    eprintln!("Go to {} and generate a new access token", link);
 
     let _ = io::stdout().flush();
-    let _ = open::that(ATLASSIAN_LINK);
+    let _ = open::that(&link);
 
     let token = prompt("Paste the token here: ")?;
-    let email = prompt("Enter you atlassian email: ")?;
+    // Personal access tokens on Jira Server are used without the email
+    let email = match config.deployment {
+        Deployment::Cloud => prompt("Enter you atlassian email: ")?,
+        Deployment::Server => String::new(),
+    };
 
     Ok(AtlassianTokens { token, email })
 }
 
-/// Parses a Jira issue id from a string
+/// Parses a Jira issue id from a string, project keys can contain digits after the first letter
 pub fn parse_issue_key(input: &str) -> IResult<&str, JiraIssueKey> {
     let (input, value) = tuple((
-        take_while1(|c: char| c.is_uppercase()),
+        recognize(pair(
+            satisfy(|c: char| c.is_uppercase()),
+            take_while(|c: char| c.is_uppercase() || c.is_ascii_digit() || c == '_'),
+        )),
         tag("-"),
         map_res(take_while1(|c: char| c.is_ascii_digit()), |n: &str| {
             n.parse::<usize>()
@@ -72,7 +81,12 @@ pub fn parse_issue_key_fuzzy(text: &str) -> Option<JiraIssueKey> {
     while !input.is_empty() {
         match try_parse_till_issue(input) {
             Ok((_, Some(value))) => return Some(value),
-            Ok((new_input, None)) => input = new_input,
+            // Skip the uppercase character that doesn't start an issue key
+            Ok((new_input, None)) => {
+                let mut chars = new_input.chars();
+                chars.next();
+                input = chars.as_str();
+            }
             Err(_) => return None,
         }
     }
@@ -93,6 +107,8 @@ mod test {
                 id: 5726
             }
         );
+        assert_eq!(parse_issue_key("A1B-12").unwrap().1.to_string(), "A1B-12");
+        assert!(parse_issue_key("1AB-12").is_err());
     }
 
     #[test]
@@ -103,6 +119,13 @@ mod test {
                 board: "DV".to_string(),
                 id: 5726
             }
-        )
+        );
+        assert_eq!(
+            parse_issue_key_fuzzy("Merge DV-12 into Main")
+                .unwrap()
+                .to_string(),
+            "DV-12"
+        );
+        assert_eq!(parse_issue_key_fuzzy("Update dependencies"), None);
     }
 }
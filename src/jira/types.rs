@@ -1,14 +1,32 @@
-use serde::Deserialize;
+use crate::error::TempomatError;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 
 #[derive(Deserialize, Debug)]
 pub struct Issue {
     pub id: String,
+    pub key: String,
     pub fields: IssueFields,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct IssueFields {
     pub summary: String,
+    /// Empty if time tracking is disabled
+    #[serde(default)]
+    pub timetracking: TimeTracking,
+}
+
+/// Issues found by a JQL search
+#[derive(Deserialize, Debug)]
+pub struct SearchResults {
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TimeTracking {
+    #[serde(rename = "remainingEstimateSeconds")]
+    pub remaining_estimate: Option<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -17,9 +35,20 @@ pub struct JiraIssueKey {
     pub id: usize,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Myself {
-    #[serde(rename = "accountId")]
+    /// The account ID on Atlassian Cloud, the user key on Jira Server
+    #[serde(rename = "accountId", alias = "key")]
+    pub account_id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+}
+
+/// Any Jira user, eg. a timesheet reviewer
+#[derive(Debug, Deserialize, Clone)]
+pub struct User {
+    /// The account ID on Atlassian Cloud, the user key on Jira Server
+    #[serde(rename = "accountId", alias = "key")]
     pub account_id: String,
     #[serde(rename = "displayName")]
     pub display_name: String,
@@ -34,8 +63,19 @@ impl<'a> From<(&'a str, &'a str, usize)> for JiraIssueKey {
     }
 }
 
-impl ToString for JiraIssueKey {
-    fn to_string(&self) -> String {
-        format!("{}-{}", self.board, self.id)
+impl fmt::Display for JiraIssueKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.board, self.id)
+    }
+}
+
+impl FromStr for JiraIssueKey {
+    type Err = TempomatError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        match super::parse_issue_key(key) {
+            Ok(("", key)) => Ok(key),
+            _ => Err(TempomatError::InvalidIssueKey(key.to_string())),
+        }
     }
 }
@@ -0,0 +1,76 @@
+use crate::{config::Saveable, timers::Timer};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Maximum amount of submissions kept in the journal
+const JOURNAL_LENGTH: usize = 50;
+
+/// A worklog that was successfully submitted to Tempo
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    /// The Tempo worklog ID
+    pub worklog_id: usize,
+    pub issue: String,
+    pub seconds: usize,
+    pub start: NaiveDateTime,
+    /// The timer this worklog was submitted from, if any
+    pub timer: Option<Timer>,
+}
+
+/// History of the submitted worklogs, newest last
+#[derive(Serialize, Deserialize, Default)]
+pub struct Journal(pub Vec<JournalEntry>);
+
+impl Journal {
+    /// Records a new submission, forgetting the oldest ones if the journal is full
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.0.push(entry);
+
+        if self.0.len() > JOURNAL_LENGTH {
+            self.0.drain(..self.0.len() - JOURNAL_LENGTH);
+        }
+    }
+
+    /// Removes a worklog from the journal, returns true if it was in it
+    pub fn forget(&mut self, worklog_id: usize) -> bool {
+        let length = self.0.len();
+        self.0.retain(|entry| entry.worklog_id != worklog_id);
+
+        self.0.len() != length
+    }
+
+    pub fn last(&self) -> Option<&JournalEntry> {
+        self.0.last()
+    }
+}
+
+impl Saveable for Journal {
+    fn path(root: &std::path::Path) -> std::path::PathBuf {
+        root.join("journal.ron")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Journal, JournalEntry, JOURNAL_LENGTH};
+    use chrono::NaiveDateTime;
+
+    #[test]
+    fn test_journal_length() {
+        let mut journal = Journal::default();
+
+        for worklog_id in 0..JOURNAL_LENGTH + 5 {
+            journal.record(JournalEntry {
+                worklog_id,
+                issue: "DV-1".to_string(),
+                seconds: 60,
+                start: NaiveDateTime::default(),
+                timer: None,
+            });
+        }
+
+        assert_eq!(journal.0.len(), JOURNAL_LENGTH);
+        assert_eq!(journal.0[0].worklog_id, 5);
+        assert_eq!(journal.last().unwrap().worklog_id, JOURNAL_LENGTH + 4);
+    }
+}
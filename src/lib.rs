@@ -1,10 +1,24 @@
 use directories::ProjectDirs;
+use reqwest::Client;
+use std::{env, path::PathBuf, sync::OnceLock};
 
 pub mod args;
+pub mod attributes;
+pub mod branches;
+pub mod cache;
+pub mod completions;
 pub mod config;
 pub mod error;
 pub mod git;
+pub mod hooks;
 pub mod jira;
+pub mod journal;
+pub mod output;
+pub mod profiles;
+pub mod prompt;
+pub mod queue;
+pub mod report;
+pub mod suggest;
 pub mod tempo;
 pub mod time;
 pub mod timers;
@@ -12,3 +26,18 @@ pub mod timers;
 pub fn dirs() -> Result<ProjectDirs, error::TempomatError> {
     ProjectDirs::from("de", "maxicarlos", "tempomat").ok_or(error::TempomatError::NoProjectDirs)
 }
+
+/// Gets the configuration root, either the given path, $TEMPOMAT_ROOT or the project directory
+pub fn config_root(config: Option<PathBuf>) -> Result<PathBuf, error::TempomatError> {
+    config
+        .or_else(|| env::var("TEMPOMAT_ROOT").map(PathBuf::from).ok())
+        .map(Ok)
+        .unwrap_or_else(|| dirs().map(|d| d.config_local_dir().to_owned()))
+}
+
+/// HTTP client shared by all requests, so that connections are reused
+pub fn http_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+
+    CLIENT.get_or_init(Client::new)
+}
@@ -1,201 +1,1867 @@
-use chrono::{Duration, Local, NaiveDateTime};
-use clap::Parser;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use clap::{CommandFactory, Parser};
+use clap_complete::{env::Shells, CompleteEnv};
 use colored::Colorize;
-use std::{env, fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process,
+};
 use tempomat::{
-    args::{CLISubcommand, TempomatCLI},
-    config::{APITokens, Config, Saveable},
-    dirs,
+    args::{
+        CLISubcommand, CacheSubcommand, HooksSubcommand, LogTime, Period, ProfileSubcommand,
+        RemainingArg, TempomatCLI, TimesheetSubcommand, TEMPO_TOKEN_ENV,
+    },
+    branches::{self, BranchLinks, BranchMatcher},
+    cache::Cache,
+    completions::COMPLETE_ENV,
+    config::{self, APITokens, CheckoutAction, Config, LocalConfig, Saveable},
+    config_root,
     error::TempomatError,
-    git,
+    git::{self, Repository},
+    hooks::{self, Installed},
     jira::{
         api::JiraApi,
-        types::{Issue, JiraIssueKey},
+        types::{Issue, JiraIssueKey, Myself, User},
+    },
+    journal::{Journal, JournalEntry},
+    output::{Event, Output},
+    profiles::{self, Profiles, DEFAULT_PROFILE},
+    prompt,
+    queue::{PendingWorklog, RemainingEstimate, WorklogQueue},
+    report, suggest,
+    tempo::{
+        api::TempoApi,
+        types::{ApprovalStatus, TimesheetApproval, Worklog},
     },
-    tempo::api::TempoApi,
     time,
-    timers::TempoTimers,
+    timers::{Started, TempoTimers, Timer},
 };
 use tracing::debug;
 
 #[tokio::main]
 async fn main() -> Result<(), TempomatError> {
-    tracing_subscriber::fmt::init();
+    // Answer completion requests from the shell before doing anything else
+    CompleteEnv::with_factory(TempomatCLI::command)
+        .var(COMPLETE_ENV)
+        .complete();
+
+    // Logs don't mix with the output, which may be a JSON document
+    tracing_subscriber::fmt().with_writer(io::stderr).init();
 
     let args = TempomatCLI::parse();
-    let Some(config_root) = args
-            .config
-            .to_owned()
-            .or_else(|| env::var("TEMPOMAT_ROOT").map(PathBuf::from).ok())
-            .or_else(|| dirs().map(|d| d.config_local_dir().to_owned()).ok()) else {
-            Err(TempomatError::NoProjectDirs)?
-        };
+    let output = Output::new(args.output);
+    let result = run(args, &output).await;
 
-    if !config_root.is_dir() {
-        fs::create_dir_all(&config_root)?;
+    if output.is_json() {
+        output.finish(result.as_ref().err())?;
+        if result.is_err() {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    result
+}
+
+async fn run(args: TempomatCLI, output: &Output) -> Result<(), TempomatError> {
+    let root = config_root(args.config.to_owned())?;
+
+    if !root.is_dir() {
+        fs::create_dir_all(&root)?;
+    }
+
+    // Settings of the repository, the profile they name is used unless one is given
+    let repo = args.repo.clone().unwrap_or_else(|| PathBuf::from("."));
+    let local_config = match Repository::discover(&repo) {
+        Ok(repository) => LocalConfig::find(&repository.work_dir)?,
+        Err(_) => None,
+    };
+
+    let mut profiles = Profiles::try_read(&root)?.unwrap_or_default();
+    let profile = args
+        .profile
+        .clone()
+        .or_else(|| local_config.as_ref()?.profile.clone())
+        .unwrap_or_else(|| profiles.default_profile().to_string());
+    let config_root = profiles::profile_root(&root, &profile)?;
+
+    // Profile commands should still work if the selected profile is gone
+    if !config_root.is_dir() && !matches!(args.command, CLISubcommand::Profile { .. }) {
+        Err(TempomatError::ProfileNotFound(profile.clone()))?
+    }
+
+    if !matches!(args.command, CLISubcommand::Sync { .. }) {
+        let pending = WorklogQueue::pending(&config_root)?;
+        if pending > 0 {
+            eprintln!(
+                "{} worklog(s) waiting to be submitted, run '{}'",
+                pending.to_string().yellow(),
+                "tempomat sync".bright_yellow()
+            );
+        }
     }
 
     let mut config = Config::try_read(&config_root)?;
-    let mut tokens = APITokens::try_read(&config_root)?;
+    if let (Some(config), Some(local_config)) = (config.as_mut(), local_config) {
+        config.merge(local_config);
+    }
+    let mut tokens = APITokens::try_read(&config_root)?.map(|mut tokens| {
+        if let Ok(token) = env::var(TEMPO_TOKEN_ENV) {
+            tokens.tempo_token = Some(token);
+        }
+        tokens
+    });
 
-    let now = Local::now().naive_local();
+    let local_now = Local::now();
+    let now = local_now.naive_local();
+    let dry_run = args.dry_run;
 
+    // Without an issue key in the branch or a default issue, the issue is picked from a Jira search
+    // if interactive
     let get_issue = |issue: Option<JiraIssueKey>| {
-        issue
-            .or_else(|| git::get_current_branch_key().ok().flatten())
-            .ok_or(TempomatError::CouldNotGetJiraIssueKey)
+        let (config, tokens, repo) = (config.as_ref(), tokens.as_ref(), &repo);
+        let config_root = &config_root;
+        let explicit_repo = args.repo.is_some();
+
+        async move {
+            if let Some(issue) = issue {
+                return Ok(issue);
+            }
+            // Outside of a repository there is just no branch, unless one was asked for
+            match branches::current_branch_key(config_root, config, repo) {
+                Ok(Some(issue)) => return Ok(issue),
+                Ok(None) => {}
+                Err(TempomatError::NoRepository) if !explicit_repo => {}
+                Err(error) => return Err(error),
+            }
+            if let Some(issue) = config.and_then(|config| config.default_issue.as_ref()) {
+                return issue.parse();
+            }
+
+            match (config, tokens) {
+                (Some(config), Some(tokens)) if !output.is_json() && io::stdin().is_terminal() => {
+                    pick_issue(config, tokens).await
+                }
+                _ => Err(TempomatError::CouldNotGetJiraIssueKey),
+            }
+        }
+    };
+
+    let workday = config.as_ref().map_or_else(
+        || Config::default().workday_seconds(),
+        Config::workday_seconds,
+    );
+    let remaining_estimate = |remaining: Option<RemainingArg>| {
+        remaining.map(|remaining| match remaining {
+            RemainingArg::Auto => RemainingEstimate::Auto,
+            RemainingArg::Duration(duration) => {
+                RemainingEstimate::Seconds(duration.seconds(workday))
+            }
+        })
     };
 
-    let requires_auth = |issue: Option<JiraIssueKey>| async move {
-        let (Some(config), Some(mut tokens)) = (config.take(), tokens.take()) else {
-                Err(TempomatError::MissingConfigurations)?
-            };
+    async fn authenticate(
+        config: Option<Config>,
+        tokens: Option<APITokens>,
+    ) -> Result<(Config, APITokens), TempomatError> {
+        let (Some(config), Some(mut tokens)) = (config, tokens) else {
+            Err(TempomatError::MissingConfigurations)?
+        };
 
         debug!("Ensuring all tokens are up to date...");
         // Ensure tokens arent outdated
         tokens.refresh_tokens().await?;
 
-        debug!("Parsing issue key");
-        let issue_key = get_issue(issue)?;
+        Ok((config, tokens))
+    }
+
+    /// Lets the user pick one of the issues matching the configured JQL
+    async fn pick_issue(
+        config: &Config,
+        tokens: &APITokens,
+    ) -> Result<JiraIssueKey, TempomatError> {
+        let issues = JiraApi(&tokens.jira, config)
+            .search(config.issue_jql())
+            .await?;
+        // There is nothing to pick, Enter would do nothing
+        if issues.is_empty() {
+            eprintln!(
+                "No issues match `issue_jql` ({})",
+                config.issue_jql().bright_yellow()
+            );
+            Err(TempomatError::CouldNotGetJiraIssueKey)?
+        }
+
+        match prompt::pick_issue(&issues)? {
+            Some(issue) => issue.key.parse(),
+            None => Err(TempomatError::CouldNotGetJiraIssueKey),
+        }
+    }
 
-        let jira_api = JiraApi(&tokens.jira, &config);
+    /// Submits a worklog, queueing it to be submitted later with `sync` if that fails. Returns
+    /// true if it was submitted
+    async fn submit_or_queue(
+        root: &Path,
+        config: Option<Config>,
+        tokens: Option<APITokens>,
+        pending: PendingWorklog,
+        dry_run: bool,
+        output: &Output,
+    ) -> Result<bool, TempomatError> {
+        let (Some(config), Some(tokens)) = (config, tokens) else {
+            Err(TempomatError::MissingConfigurations)?
+        };
 
-        debug!("Getting issue key and user information...");
-        let jira_issue = jira_api.get_issue(&issue_key).await?;
-        let me = jira_api.get_me().await?;
+        if dry_run {
+            let (config, tokens) = authenticate(Some(config), Some(tokens)).await?;
+            let (payload, jira_issue) = pending.preview(root, &config, &tokens).await?;
 
-        Result::<_, TempomatError>::Ok((jira_issue, me, tokens, issue_key))
-    };
+            output.print(format_args!(
+                "Dry run, would log {} for issue '{}' with:",
+                time::seconds_to_string(pending.seconds).green(),
+                jira_issue.fields.summary.bright_blue()
+            ));
+            output.print(serde_json::to_string_pretty(&payload)?);
+            show_remaining_estimate(output, &pending, &jira_issue);
+            output.emit(Event::WorklogPreview {
+                issue: pending.issue,
+                payload,
+            });
+            return Ok(false);
+        }
 
-    fn show_worklog_result(result: bool, time: &str, issue: &Issue) {
-        if result {
-            println!(
-                "Successfully logged {} for issue '{}'",
-                time.green(),
-                issue.fields.summary.bright_blue()
-            );
+        let submission = async {
+            let (config, tokens) = authenticate(Some(config), Some(tokens)).await?;
+            pending.submit(root, &config, &tokens).await
+        }
+        .await;
+
+        let error = match submission {
+            // Submitting again would fail the same way
+            Err(
+                error @ (TempomatError::UnknownAttribute(_)
+                | TempomatError::InvalidAttributeValue { .. }
+                | TempomatError::MissingAttribute(_)),
+            ) => Err(error)?,
+            Ok((Some(worklog), jira_issue)) => {
+                show_worklog_result(
+                    output,
+                    &worklog,
+                    &time::seconds_to_string(pending.seconds),
+                    &jira_issue,
+                );
+                show_remaining_estimate(output, &pending, &jira_issue);
+                output.emit(Event::WorklogCreated {
+                    id: worklog.id,
+                    issue: pending.issue.clone(),
+                    seconds: worklog.time_spent,
+                    start: worklog.start(),
+                });
+                record_submission(root, &worklog, &pending.issue, pending.timer)?;
+                return Ok(true);
+            }
+            Ok((None, _)) => "the worklog was rejected, check logs".to_string(),
+            Err(error) => error.to_string(),
+        };
+
+        output.print(format_args!(
+            "{} '{}': {error}",
+            "Failed to submit the worklog for issue".red(),
+            pending.issue.bright_blue()
+        ));
+
+        output.emit(Event::WorklogQueued {
+            issue: pending.issue.clone(),
+            seconds: pending.seconds,
+            start: pending.start,
+            error,
+        });
+
+        let mut queue = WorklogQueue::try_read(root)?.unwrap_or_default();
+        queue.0.push(pending);
+        queue.save(root)?;
+
+        output.print(format_args!(
+            "It was queued, submit it later using '{}'",
+            "tempomat sync".bright_yellow()
+        ));
+
+        Ok(false)
+    }
+
+    /// Work attribute values for a worklog of `issue`, including the project's defaults
+    fn worklog_attributes(
+        config: Option<&Config>,
+        issue: &JiraIssueKey,
+        given: Vec<(String, String)>,
+    ) -> Vec<(String, String)> {
+        match config {
+            Some(config) => config.worklog_attributes(&issue.board, given),
+            None => given,
+        }
+    }
+
+    fn describe(
+        config: Option<&Config>,
+        issue: &JiraIssueKey,
+        given: Option<String>,
+    ) -> Option<String> {
+        match config {
+            Some(config) => config.describe(issue, given),
+            None => given,
+        }
+    }
+
+    fn show_worklog_result(output: &Output, worklog: &Worklog, time: &str, issue: &Issue) {
+        output.print(format_args!(
+            "Successfully logged {} for issue '{}' (worklog {})",
+            time.green(),
+            issue.fields.summary.bright_blue(),
+            worklog.id.to_string().bright_yellow()
+        ));
+    }
+
+    /// Shows the remaining estimate `issue` had before the worklog, and what it is changed to
+    fn show_remaining_estimate(output: &Output, pending: &PendingWorklog, issue: &Issue) {
+        let current = issue.fields.timetracking.remaining_estimate;
+
+        match (current, pending.time(issue).remaining) {
+            (current, Some(remaining)) => output.print(format_args!(
+                "Remaining estimate: {} -> {}",
+                current.map_or("none".to_string(), time::seconds_to_string),
+                time::seconds_to_string(remaining).green()
+            )),
+            (Some(current), None) => output.print(format_args!(
+                "Remaining estimate before this worklog: {} (set it with --remaining)",
+                time::seconds_to_string(current).green()
+            )),
+            (None, None) => {}
+        }
+    }
+
+    fn record_submission(
+        root: &Path,
+        worklog: &Worklog,
+        issue: &str,
+        timer: Option<Timer>,
+    ) -> Result<(), TempomatError> {
+        let mut journal = Journal::try_read(root)?.unwrap_or_default();
+
+        journal.record(JournalEntry {
+            worklog_id: worklog.id,
+            issue: issue.to_string(),
+            seconds: worklog.time_spent,
+            start: worklog.start(),
+            timer,
+        });
+
+        journal.save(root)
+    }
+
+    /// Finds a user by their account ID, name or email address
+    async fn find_user(jira_api: &JiraApi<'_>, query: &str) -> Result<User, TempomatError> {
+        let mut users = jira_api.search_users(query).await?;
+
+        match users.len() {
+            1 => Ok(users.remove(0)),
+            // Account IDs and user keys are not always found by the search
+            0 => jira_api
+                .get_user(query)
+                .await
+                .map_err(|_| TempomatError::UserNotFound(query.to_string())),
+            _ => users
+                .into_iter()
+                .find(|user| user.account_id == query || user.display_name == query)
+                .ok_or_else(|| TempomatError::AmbiguousUser(query.to_string())),
+        }
+    }
+
+    async fn show_approval(
+        output: &Output,
+        jira_api: &JiraApi<'_>,
+        approval: &TimesheetApproval,
+    ) -> Result<(), TempomatError> {
+        let reviewer = match &approval.reviewer {
+            Some(reviewer) => Some(
+                jira_api
+                    .get_user(&reviewer.account_id)
+                    .await
+                    .map(|user| user.display_name)
+                    .unwrap_or_else(|_| reviewer.account_id.clone()),
+            ),
+            None => None,
+        };
+        let status = approval.status.to_string();
+
+        output.print(format_args!(
+            "Timesheet from {} to {}: {}",
+            approval.period.from,
+            approval.period.to,
+            match approval.status {
+                ApprovalStatus::Open => status.bright_yellow(),
+                ApprovalStatus::InReview => status.bright_blue(),
+                ApprovalStatus::Approved => status.green(),
+                ApprovalStatus::Rejected => status.red(),
+                ApprovalStatus::Unknown => status.normal(),
+            }
+        ));
+        output.print(format_args!(
+            "Reviewer: {}",
+            reviewer.as_deref().unwrap_or("none")
+        ));
+        output.print(format_args!(
+            "Logged {} of {} required ({})",
+            time::seconds_to_string(approval.time_spent).green(),
+            time::seconds_to_string(approval.required),
+            time::balance_to_string(approval.time_spent as i64 - approval.required as i64)
+        ));
+        output.emit(Event::Timesheet {
+            from: approval.period.from,
+            to: approval.period.to,
+            status: approval.status,
+            reviewer,
+            logged_seconds: approval.time_spent,
+            required_seconds: approval.required,
+        });
+
+        Ok(())
+    }
+
+    /// Starts a timer for `issue`, or resumes its paused timer so that its time is kept. Returns
+    /// nothing if the timer is already running
+    fn start_timer(timers: &mut TempoTimers, issue: &str, now: NaiveDateTime) -> Option<Event> {
+        let issue = issue.to_string();
+        match timers.start(&issue, now) {
+            Started::New => Some(Event::TimerStarted { issue, start: now }),
+            Started::Resumed => Some(Event::TimerResumed { issue }),
+            Started::AlreadyRunning => None,
+        }
+    }
+
+    fn show_timer_started(output: &Output, event: Event) {
+        match &event {
+            Event::TimerStarted { issue, start } => output.print(format_args!(
+                "Started timer on {} for issue {}",
+                start.to_string().bright_yellow(),
+                issue.blue()
+            )),
+            Event::TimerResumed { issue } => output.print(format_args!(
+                "Resumed timer for issue '{}'",
+                issue.bright_blue()
+            )),
+            _ => {}
+        }
+        output.emit(event);
+    }
+
+    /// The current user from the cache, fetched if it expired
+    async fn cached_me(
+        root: &Path,
+        config: &Config,
+        tokens: &APITokens,
+        now: NaiveDateTime,
+        dry_run: bool,
+    ) -> Result<Myself, TempomatError> {
+        let mut cache = Cache::read(root, &config.jira_url()?, &tokens.jira.email)?;
+        let me = cache
+            .me(&JiraApi(&tokens.jira, config), now, config.cache_ttl())
+            .await?;
+        save_cache(root, &cache, dry_run)?;
+
+        Ok(me)
+    }
+
+    /// Saves the lookups, unless this is a dry run
+    fn save_cache(root: &Path, cache: &Cache, dry_run: bool) -> Result<(), TempomatError> {
+        if dry_run {
+            Ok(())
+        } else {
+            cache.save(root)
+        }
+    }
+
+    /// Saves the timers, unless this is a dry run
+    fn save_timers(
+        root: &Path,
+        timers: &TempoTimers,
+        dry_run: bool,
+        output: &Output,
+    ) -> Result<(), TempomatError> {
+        if dry_run {
+            output.print("Dry run, the timers were not changed".dimmed());
+            Ok(())
+        } else {
+            timers.save(root)
+        }
+    }
+
+    /// Applies `change` to the timer of `issue` or to all timers, `change` returns false if the
+    /// timer was not changed. Returns the issues of the changed timers
+    fn change_timers(
+        root: &Path,
+        issue: Option<JiraIssueKey>,
+        mut change: impl FnMut(&mut Timer) -> bool,
+        action: &str,
+        dry_run: bool,
+        output: &Output,
+    ) -> Result<Vec<String>, TempomatError> {
+        let mut timers = TempoTimers::try_read(root)?.unwrap_or_default();
+        let mut changed = Vec::new();
+
+        if let Some(issue) = issue {
+            let issue = issue.to_string();
+            let timer = timers
+                .0
+                .get_mut(&issue)
+                .ok_or(TempomatError::TimerInvalid)?;
+
+            if change(timer) {
+                changed.push(issue);
+            }
         } else {
-            println!("{}", "Failed to create worklog, check logs".red());
+            for (issue, timer) in &mut timers.0 {
+                if change(timer) {
+                    changed.push(issue.clone());
+                }
+            }
+        }
+
+        if changed.is_empty() {
+            output.print("No timer was changed.");
+            return Ok(changed);
         }
+
+        save_timers(root, &timers, dry_run, output)?;
+        changed.sort();
+        for issue in &changed {
+            output.print(format_args!(
+                "{action} timer for issue '{}'",
+                issue.bright_blue()
+            ));
+        }
+
+        Ok(changed)
     }
 
     'cmd: {
         match args.command {
             CLISubcommand::Log {
                 time,
+                at,
+                date,
                 description,
+                attributes,
+                account,
+                billable,
+                remaining,
                 issue,
             } => {
-                let (jira_issue, me, tokens, _) = requires_auth(issue).await?;
-                let start = now - Duration::seconds(time.0 as i64);
+                let issue_key = get_issue(issue).await?;
+                let date = date.map(|date| date.resolve(now.date()));
 
-                debug!("Submitting the worklog");
-                let result = TempoApi(&tokens.tempo.tokens)
-                    .create_worklog(&me, &jira_issue.id, description, time.0, start)
-                    .await?;
+                // Computed with instants, so that daylight saving time changes are respected
+                let (seconds, start) = match time {
+                    LogTime::Range(_, _) if at.is_some() => Err(TempomatError::StartWithTimeRange)?,
+                    LogTime::Range(from, to) => {
+                        let date = date.unwrap_or(now.date());
+                        let (start, seconds) = time::time_range(&Local, date, from, to)?;
+                        (seconds, start)
+                    }
+                    LogTime::Duration(duration) => {
+                        let seconds = duration.seconds(workday);
+                        let start = match (at, date) {
+                            (Some(at), date) => time::local_instant(
+                                &Local,
+                                date.unwrap_or(now.date()).and_time(at),
+                            )?,
+                            (None, Some(date)) => {
+                                time::local_instant(&Local, date.and_time(now.time()))?
+                                    - Duration::seconds(seconds as i64)
+                            }
+                            (None, None) => local_now - Duration::seconds(seconds as i64),
+                        };
+                        (seconds, start)
+                    }
+                };
+
+                time::validate_worklog(&start, seconds, &local_now)?;
+                let start = start.naive_local();
 
-                show_worklog_result(result, &time.1, &jira_issue);
+                let pending = PendingWorklog {
+                    issue: issue_key.to_string(),
+                    seconds,
+                    start,
+                    description: describe(config.as_ref(), &issue_key, description),
+                    timer: None,
+                    attributes: worklog_attributes(config.as_ref(), &issue_key, attributes),
+                    account,
+                    billable: billable.map(|billable| billable.seconds(workday)),
+                    remaining: remaining_estimate(remaining),
+                };
+                submit_or_queue(
+                    &config_root,
+                    config.take(),
+                    tokens.take(),
+                    pending,
+                    dry_run,
+                    output,
+                )
+                .await?;
             }
-            CLISubcommand::Login { atlassian_instance } => {
-                let config = Config { atlassian_instance };
-                let access_tokens = APITokens::initialize(&config).await?;
+            CLISubcommand::Login {
+                atlassian_instance,
+                jira_url,
+                tempo_url,
+                deployment,
+                tempo_token,
+            } => {
+                // Keep the other settings when logging in again, read again since `config` has the
+                // repository's settings merged in
+                let config = Config {
+                    atlassian_instance,
+                    jira_url,
+                    tempo_url,
+                    deployment,
+                    ..Config::try_read(&config_root)?.unwrap_or_default()
+                };
+                if dry_run {
+                    output.print(format_args!(
+                        "Dry run, would log in to {} and save the tokens",
+                        config.jira_url()?
+                    ));
+                    break 'cmd;
+                }
+                let access_tokens = APITokens::initialize(&config, tempo_token).await?;
 
                 config.save(&config_root)?;
                 access_tokens.save(&config_root)?;
+                // The cached user and issues may be of another account or Jira
+                Cache::clear(&config_root)?;
+                output.emit(Event::LoggedIn {
+                    jira_url: config.jira_url()?,
+                });
             }
             CLISubcommand::Start { issue } => {
-                let issue = get_issue(issue)?;
-                let mut timers = if let Some(timers) = TempoTimers::try_read(&config_root)? {
-                    timers
-                } else {
-                    Default::default()
-                };
+                let issue = get_issue(issue).await?.to_string();
+                let mut timers = TempoTimers::try_read(&config_root)?.unwrap_or_default();
 
-                timers.0.insert(issue.to_string(), now.to_owned());
-                timers.save(&config_root)?;
-
-                println!(
-                    "Started timer on {} for issue {}",
-                    now.to_string().bright_yellow(),
-                    issue.to_string().blue()
-                );
+                match start_timer(&mut timers, &issue, now) {
+                    Some(started) => {
+                        save_timers(&config_root, &timers, dry_run, output)?;
+                        show_timer_started(output, started);
+                    }
+                    None => output.print(format_args!(
+                        "The timer for issue '{}' is already running",
+                        issue.bright_blue()
+                    )),
+                }
             }
             CLISubcommand::Stop {
                 no_submit,
                 description,
+                attributes,
+                account,
+                billable,
+                remaining,
                 issue,
             } => {
                 let mut timers = TempoTimers::try_read(&config_root)?
                     .ok_or(TempomatError::MissingConfigurations)?;
-                let issue = get_issue(issue.clone())?;
+                let issue = get_issue(issue.clone()).await?;
                 let issue_text = issue.to_string();
-                let Some(start) = timers.0.get(&issue_text) else {
+                let Some(timer) = timers.0.remove(&issue_text) else {
                     Err(TempomatError::TimerInvalid)?
                 };
-                let start = start.to_owned();
-
-                if timers.0.remove(&issue_text).is_none() {
-                    println!(
-                        "There was no timer for the issue '{}'",
-                        issue_text.bright_blue()
-                    );
-                    break 'cmd;
-                }
+                let start = timer.start().ok_or(TempomatError::TimerInvalid)?;
 
                 if !no_submit {
-                    let (jira_issue, me, tokens, _) = requires_auth(Some(issue)).await?;
-
-                    let til_now = (now - start)
+                    // Paused time is not logged
+                    let til_now = timer
+                        .elapsed(now)
                         .num_seconds()
                         .try_into()
                         .map_err(|_| TempomatError::NegativeTime)?;
-                    let result = TempoApi(&tokens.tempo.tokens)
-                        .create_worklog(&me, &jira_issue.id, description, til_now, start)
-                        .await?;
+                    let pending = PendingWorklog {
+                        issue: issue_text,
+                        seconds: til_now,
+                        start,
+                        description: describe(config.as_ref(), &issue, description),
+                        timer: Some(timer),
+                        attributes: worklog_attributes(config.as_ref(), &issue, attributes),
+                        account,
+                        billable: billable.map(|billable| billable.seconds(workday)),
+                        remaining: remaining_estimate(remaining),
+                    };
+
+                    // Once submitted or queued, the timer is no longer needed
+                    submit_or_queue(
+                        &config_root,
+                        config.take(),
+                        tokens.take(),
+                        pending,
+                        dry_run,
+                        output,
+                    )
+                    .await?;
+                    save_timers(&config_root, &timers, dry_run, output)?;
+                } else {
+                    save_timers(&config_root, &timers, dry_run, output)?;
+                    output.print(format_args!(
+                        "Cancelled timer for issue '{}'",
+                        issue_text.bright_blue()
+                    ));
+                    output.emit(Event::TimerCancelled { issue: issue_text });
+                }
+            }
+            CLISubcommand::Switch {
+                issue,
+                from,
+                keep_running,
+                description,
+            } => {
+                let issue = get_issue(issue).await?;
+                let issue_text = issue.to_string();
+                let mut timers = TempoTimers::try_read(&config_root)?.unwrap_or_default();
 
-                    if result {
-                        timers.save(&config_root)?;
+                if keep_running {
+                    match start_timer(&mut timers, &issue_text, now) {
+                        Some(started) => {
+                            save_timers(&config_root, &timers, dry_run, output)?;
+                            show_timer_started(output, started);
+                        }
+                        None => output.print(format_args!(
+                            "The timer for issue '{}' is already running",
+                            issue_text.bright_blue()
+                        )),
                     }
+                    break 'cmd;
+                }
 
-                    show_worklog_result(result, &time::seconds_to_string(til_now), &jira_issue);
+                let from = match (from, timers.running().as_slice()) {
+                    (Some(from), _) => from,
+                    (None, [running]) => running.parse()?,
+                    (None, []) => Err(TempomatError::NoRunningTimer)?,
+                    (None, _) => Err(TempomatError::AmbiguousTimer)?,
+                };
+                let from_text = from.to_string();
+                if from_text == issue_text {
+                    output.print(format_args!(
+                        "The timer for issue '{}' is already running",
+                        issue_text.bright_blue()
+                    ));
+                    break 'cmd;
+                }
+
+                let timer = timers
+                    .0
+                    .remove(&from_text)
+                    .ok_or(TempomatError::TimerInvalid)?;
+                let start = timer.start().ok_or(TempomatError::TimerInvalid)?;
+                let til_now: usize = timer
+                    .elapsed(now)
+                    .num_seconds()
+                    .try_into()
+                    .map_err(|_| TempomatError::NegativeTime)?;
+
+                // The new timer starts at the same instant the old one stops
+                let started = start_timer(&mut timers, &issue_text, now);
+
+                let pending = PendingWorklog {
+                    issue: from_text,
+                    seconds: til_now,
+                    start,
+                    description: describe(config.as_ref(), &from, description),
+                    timer: Some(timer),
+                    attributes: worklog_attributes(config.as_ref(), &from, Vec::new()),
+                    account: None,
+                    billable: None,
+                    remaining: None,
+                };
+                submit_or_queue(
+                    &config_root,
+                    config.take(),
+                    tokens.take(),
+                    pending,
+                    dry_run,
+                    output,
+                )
+                .await?;
+                save_timers(&config_root, &timers, dry_run, output)?;
+                // Only once the timers are saved, the worklog may have been rejected
+                if let Some(started) = started {
+                    show_timer_started(output, started);
+                }
+            }
+            CLISubcommand::Pause { all, issue } => {
+                let issue = if all {
+                    None
+                } else {
+                    Some(get_issue(issue).await?)
+                };
+                let changed = change_timers(
+                    &config_root,
+                    issue,
+                    |timer| timer.pause(now),
+                    "Paused",
+                    dry_run,
+                    output,
+                )?;
+                for issue in changed {
+                    output.emit(Event::TimerPaused { issue });
+                }
+            }
+            CLISubcommand::Resume { all, issue } => {
+                let issue = if all {
+                    None
                 } else {
-                    timers.save(&config_root)?;
-                    println!("Cancelled timer for issue '{}'", issue_text.bright_blue());
+                    Some(get_issue(issue).await?)
+                };
+                let changed = change_timers(
+                    &config_root,
+                    issue,
+                    |timer| timer.resume(now),
+                    "Resumed",
+                    dry_run,
+                    output,
+                )?;
+                for issue in changed {
+                    output.emit(Event::TimerResumed { issue });
                 }
             }
             CLISubcommand::List { all, issue } => {
-                let show_all_message =
-                    || println!("Use '{}' to show all running timers.", "-a".bright_yellow());
-
-                let show_timer = |issue: &str, timer: NaiveDateTime| {
-                    let elapsed = (now - timer).num_seconds() as usize; // Hopefully we dont get any negative times here
-                    println!(
-                        "  '{}' has been running for {}",
-                        issue.bright_blue(),
-                        time::seconds_to_string(elapsed)
-                    );
+                let show_all_message = || {
+                    output.print(format_args!(
+                        "Use '{}' to show all running timers.",
+                        "-a".bright_yellow()
+                    ))
+                };
+
+                let show_timer = |issue: &str, timer: &Timer| {
+                    output.emit(Event::Timer {
+                        issue: issue.to_string(),
+                        running: timer.is_running(),
+                        elapsed_seconds: timer.elapsed(now).num_seconds(),
+                        start: timer.start(),
+                    });
+
+                    let elapsed = timer.elapsed(now).num_seconds() as usize; // Hopefully we dont get any negative times here
+                    if timer.is_running() {
+                        output.print(format_args!(
+                            "  '{}' has been running for {}",
+                            issue.bright_blue(),
+                            time::seconds_to_string(elapsed)
+                        ));
+                    } else {
+                        output.print(format_args!(
+                            "  '{}' is paused after running for {}",
+                            issue.bright_blue(),
+                            time::seconds_to_string(elapsed)
+                        ));
+                    }
                 };
 
                 let Some(timers) = TempoTimers::try_read(&config_root)? else {
                     // If there are no timers, show nothing
-                    println!("No timer has been started yet.");
+                    output.print("No timer has been started yet.");
                     break 'cmd;
                 };
 
                 if !all {
-                    let issue = get_issue(issue)?.to_string();
-                    let Some(timer )= timers.0.get(&issue) else {
-                        println!("{}", "No timer with that id has been found!".red());
+                    let issue = get_issue(issue).await?.to_string();
+                    let Some(timer) = timers.0.get(&issue) else {
+                        output.print("No timer with that id has been found!".red());
                         show_all_message();
                         break 'cmd;
                     };
 
-                    show_timer(&issue, timer.to_owned());
+                    show_timer(&issue, timer);
                 } else if timers.0.is_empty() {
-                    println!("No currently running timers!")
+                    output.print("No currently running timers!")
+                } else {
+                    for (issue, timer) in &timers.0 {
+                        show_timer(issue, timer);
+                    }
+                }
+            }
+            CLISubcommand::Issues { jql } => {
+                let (Some(config), Some(tokens)) = (config.take(), tokens.take()) else {
+                    Err(TempomatError::MissingConfigurations)?
+                };
+                let issues = JiraApi(&tokens.jira, &config)
+                    .search(jql.as_deref().unwrap_or(config.issue_jql()))
+                    .await?;
+
+                if issues.is_empty() {
+                    output.print("No issues found.");
+                }
+
+                for issue in issues {
+                    output.print(format_args!(
+                        "{}  {}",
+                        issue.key.bright_yellow(),
+                        issue.fields.summary
+                    ));
+                    output.emit(Event::Issue {
+                        key: issue.key,
+                        summary: issue.fields.summary,
+                    });
+                }
+            }
+            CLISubcommand::Worklogs {
+                from,
+                to,
+                week,
+                yesterday,
+            } => {
+                let today = now.date();
+                let (from, to) = if week {
+                    time::week_range(today)
+                } else if yesterday {
+                    let yesterday = today - Duration::days(1);
+                    (yesterday, yesterday)
                 } else {
-                    for (issue, started) in timers.0 {
-                        show_timer(&issue, started.to_owned());
+                    let from = from.unwrap_or(today);
+                    (from, to.unwrap_or(from.max(today)))
+                };
+
+                if from > to {
+                    Err(TempomatError::InvalidDateRange)?
+                }
+
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+                let jira_api = JiraApi(&tokens.jira, &config);
+                let mut cache = Cache::read(&config_root, &config.jira_url()?, &tokens.jira.email)?;
+                let me = cache.me(&jira_api, now, config.cache_ttl()).await?;
+
+                debug!("Fetching worklogs from {from} to {to}");
+                let worklogs = TempoApi(tokens.tempo_access_token()?, &config)
+                    .search_worklogs(&me, from, to)
+                    .await?;
+
+                if worklogs.is_empty() {
+                    output.print(format_args!("No worklogs found between {from} and {to}."));
+                    break 'cmd;
+                }
+
+                // Tempo only returns issue IDs, the keys and summaries come from Jira
+                let ids = worklogs.iter().map(|worklog| worklog.issue.id);
+                let issues = cache
+                    .issues_by_id(&jira_api, ids, now, config.cache_ttl())
+                    .await?;
+                save_cache(&config_root, &cache, dry_run)?;
+
+                let mut days: BTreeMap<NaiveDate, Vec<&Worklog>> = BTreeMap::new();
+                for worklog in &worklogs {
+                    days.entry(worklog.start_date).or_default().push(worklog);
+                }
+
+                for (day, mut worklogs) in days {
+                    worklogs.sort_by_key(|worklog| worklog.start_time);
+                    let total = worklogs.iter().map(|worklog| worklog.time_spent).sum();
+
+                    output.print(format_args!(
+                        "{} ({})",
+                        day.format("%A %Y-%m-%d").to_string().bold(),
+                        time::seconds_to_string(total).green()
+                    ));
+
+                    for worklog in worklogs {
+                        let issue = &issues[&worklog.issue.id];
+                        output.emit(Event::Worklog {
+                            id: worklog.id,
+                            issue: issue.key.clone(),
+                            summary: issue.fields.summary.clone(),
+                            seconds: worklog.time_spent,
+                            start: worklog.start(),
+                            description: worklog.description.clone(),
+                        });
+                        output.print(format_args!(
+                            "  {} {:>8}  {} {}",
+                            worklog.start_time.format("%H:%M"),
+                            time::seconds_to_string(worklog.time_spent).green(),
+                            issue.key.bright_blue(),
+                            issue.fields.summary
+                        ));
+                        if let Some(description) = worklog
+                            .description
+                            .as_deref()
+                            .filter(|description| !description.is_empty())
+                        {
+                            output
+                                .print(format_args!("                  {}", description.dimmed()));
+                        }
+                    }
+                }
+            }
+            CLISubcommand::Report { week } => {
+                let today = now.date();
+                let (from, to) = if week {
+                    time::week_range(today)
+                } else {
+                    (today, today)
+                };
+
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+                let me = cached_me(&config_root, &config, &tokens, now, dry_run).await?;
+                let tempo_api = TempoApi(tokens.tempo_access_token()?, &config);
+
+                debug!("Fetching worklogs and schedule from {from} to {to}");
+                let worklogs = tempo_api.search_worklogs(&me, from, to).await?;
+                let schedule = tempo_api.get_user_schedule(&me, from, to).await?;
+                let timers = TempoTimers::try_read(&config_root)?.unwrap_or_default();
+                let days = report::daily_report(from, to, &worklogs, &schedule, &timers, now);
+
+                output.print(format_args!(
+                    "{:<16} {:>8} {:>8} {:>8} {:>8}",
+                    "Day".bold(),
+                    "Logged".bold(),
+                    "Running".bold(),
+                    "Required".bold(),
+                    "Balance".bold()
+                ));
+                for day in &days {
+                    output.emit(Event::ReportDay {
+                        date: day.date,
+                        logged_seconds: day.logged,
+                        running_seconds: day.running,
+                        required_seconds: day.required,
+                        balance_seconds: day.balance(),
+                    });
+
+                    let line = format!(
+                        "{:<16} {:>8} {:>8} {:>8} {:>8}",
+                        day.date.format("%a %Y-%m-%d"),
+                        time::seconds_to_string(day.logged),
+                        time::seconds_to_string(day.running),
+                        time::seconds_to_string(day.required),
+                        time::balance_to_string(day.balance())
+                    );
+                    // Days still ahead can't be under target yet
+                    if day.date > today {
+                        output.print(line.dimmed());
+                    } else if day.balance() < 0 {
+                        output.print(line.red());
+                    } else {
+                        output.print(line);
+                    }
+                }
+
+                let total = |field: fn(&report::DayReport) -> usize| -> usize {
+                    days.iter().map(field).sum()
+                };
+                let (logged, running, required) = (
+                    total(|day| day.logged),
+                    total(|day| day.running),
+                    total(|day| day.required),
+                );
+                let balance = (logged + running) as i64 - required as i64;
+                let balance_text = time::balance_to_string(balance);
+
+                output.print(format_args!(
+                    "{:<16} {:>8} {:>8} {:>8} {:>8}",
+                    "Total".bold(),
+                    time::seconds_to_string(logged),
+                    time::seconds_to_string(running),
+                    time::seconds_to_string(required),
+                    if balance < 0 {
+                        balance_text.red()
+                    } else {
+                        balance_text.green()
+                    }
+                ));
+            }
+            CLISubcommand::Edit {
+                id,
+                time,
+                date,
+                at,
+                description,
+                issue,
+            } => {
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+                let tempo_api = TempoApi(tokens.tempo_access_token()?, &config);
+                let mut worklog = tempo_api.get_worklog(id).await?;
+
+                if let Some(time) = time {
+                    let seconds = time.seconds(config.workday_seconds());
+                    // Billable time that was all the time spent keeps following it
+                    if worklog.billable == Some(worklog.time_spent) {
+                        worklog.billable = Some(seconds);
+                    }
+                    worklog.time_spent = seconds;
+                }
+                if let Some(date) = date {
+                    worklog.start_date = date.resolve(now.date());
+                }
+                if let Some(at) = at {
+                    worklog.start_time = at;
+                }
+                if description.is_some() {
+                    worklog.description = description;
+                }
+                let mut issue_key = None;
+                if let Some(issue) = issue {
+                    let jira_issue = JiraApi(&tokens.jira, &config).get_issue(&issue).await?;
+                    worklog.issue.id = jira_issue
+                        .id
+                        .parse()
+                        .map_err(|_| TempomatError::InvalidIssueId(jira_issue.id))?;
+                    issue_key = Some(jira_issue.key);
+                }
+
+                let start = time::local_instant(&Local, worklog.start())?;
+                time::validate_worklog(&start, worklog.time_spent, &local_now)?;
+
+                if dry_run {
+                    output.print(format_args!(
+                        "Dry run, would update worklog {} with:",
+                        worklog.id.to_string().bright_yellow()
+                    ));
+                    let payload = tempo_api.preview_payload(
+                        &worklog.author.account_id,
+                        &worklog.issue.id.to_string(),
+                        worklog.time(),
+                        worklog.description.clone(),
+                        &worklog.attributes.values,
+                    )?;
+                    output.print(serde_json::to_string_pretty(&payload)?);
+                    let issue = match issue_key {
+                        Some(key) => key,
+                        None => {
+                            JiraApi(&tokens.jira, &config)
+                                .get_issue_by_id(worklog.issue.id)
+                                .await?
+                                .key
+                        }
+                    };
+                    output.emit(Event::WorklogPreview { issue, payload });
+                    break 'cmd;
+                }
+
+                debug!("Submitting the updated worklog");
+                let worklog = tempo_api
+                    .update_worklog(&worklog)
+                    .await?
+                    .ok_or(TempomatError::WorklogRejected(id))?;
+                output.emit(Event::WorklogUpdated {
+                    id: worklog.id,
+                    seconds: worklog.time_spent,
+                    start: worklog.start(),
+                });
+                output.print(format_args!(
+                    "Updated worklog {}: {} on {} at {}",
+                    worklog.id.to_string().bright_yellow(),
+                    time::seconds_to_string(worklog.time_spent).green(),
+                    worklog.start_date,
+                    worklog.start_time.format("%H:%M")
+                ));
+            }
+            CLISubcommand::Delete { id, yes } => {
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+                let tempo_api = TempoApi(tokens.tempo_access_token()?, &config);
+                let worklog = tempo_api.get_worklog(id).await?;
+                let jira_issue = JiraApi(&tokens.jira, &config)
+                    .get_issue_by_id(worklog.issue.id)
+                    .await?;
+
+                output.print(format_args!(
+                    "Worklog {}: {} on {} at {} for '{}'",
+                    worklog.id.to_string().bright_yellow(),
+                    time::seconds_to_string(worklog.time_spent).green(),
+                    worklog.start_date,
+                    worklog.start_time.format("%H:%M"),
+                    jira_issue.key.bright_blue()
+                ));
+
+                if dry_run {
+                    output.print("Dry run, not deleting anything.");
+                    break 'cmd;
+                }
+
+                if !yes && !prompt::confirm("Delete this worklog?")? {
+                    output.print("Not deleting anything.");
+                    break 'cmd;
+                }
+
+                if !tempo_api.delete_worklog(id).await? {
+                    Err(TempomatError::WorklogRejected(id))?
+                }
+
+                // Don't let `undo` try to delete it again
+                if let Some(mut journal) = Journal::try_read(&config_root)? {
+                    if journal.forget(id) {
+                        journal.save(&config_root)?;
+                    }
+                }
+
+                output.print(format_args!(
+                    "Deleted worklog {}",
+                    id.to_string().bright_yellow()
+                ));
+                output.emit(Event::WorklogDeleted { id });
+            }
+            CLISubcommand::Profile { command } => match command {
+                ProfileSubcommand::List => {
+                    for name in profiles::list(&root)? {
+                        output.emit(Event::Profile {
+                            default: name == profiles.default_profile(),
+                            name: name.clone(),
+                        });
+                        if name == profiles.default_profile() {
+                            output.print(format_args!(
+                                "{} {}",
+                                name.bright_blue(),
+                                "(default)".dimmed()
+                            ));
+                        } else {
+                            output.print(&name);
+                        }
+                    }
+                }
+                ProfileSubcommand::Add { name } => {
+                    let profile_root = profiles::profile_root(&root, &name)?;
+                    if profile_root.is_dir() {
+                        Err(TempomatError::ProfileExists(name.clone()))?
+                    }
+                    if dry_run {
+                        output.print(format_args!(
+                            "Dry run, would create profile '{}'",
+                            name.bright_blue()
+                        ));
+                        break 'cmd;
+                    }
+
+                    fs::create_dir_all(&profile_root)?;
+                    output.print(format_args!(
+                        "Created profile '{}', use '{}' to log in",
+                        name.bright_blue(),
+                        format!("tempomat --profile {name} login").bright_yellow()
+                    ));
+                    output.emit(Event::ProfileAdded { name });
+                }
+                ProfileSubcommand::Remove { name, yes } => {
+                    if name == DEFAULT_PROFILE {
+                        Err(TempomatError::InvalidProfileName(name.clone()))?
+                    }
+
+                    let profile_root = profiles::profile_root(&root, &name)?;
+                    if !profile_root.is_dir() {
+                        Err(TempomatError::ProfileNotFound(name.clone()))?
+                    }
+                    if dry_run {
+                        output.print(format_args!(
+                            "Dry run, would remove profile '{}' with all its tokens and timers",
+                            name.bright_blue()
+                        ));
+                        break 'cmd;
+                    }
+
+                    if !yes
+                        && !prompt::confirm(&format!(
+                            "Remove profile '{name}' with all its tokens and timers?"
+                        ))?
+                    {
+                        output.print("Not removing anything.");
+                        break 'cmd;
+                    }
+
+                    fs::remove_dir_all(&profile_root)?;
+                    if profiles.default.as_deref() == Some(name.as_str()) {
+                        profiles.default = None;
+                        profiles.save(&root)?;
+                    }
+
+                    output.print(format_args!("Removed profile '{}'", name.bright_blue()));
+                    output.emit(Event::ProfileRemoved { name });
+                }
+                ProfileSubcommand::Default { name } => {
+                    if !profiles::profile_root(&root, &name)?.is_dir() {
+                        Err(TempomatError::ProfileNotFound(name.clone()))?
+                    }
+                    if dry_run {
+                        output.print(format_args!(
+                            "Dry run, would use profile '{}' by default",
+                            name.bright_blue()
+                        ));
+                        break 'cmd;
+                    }
+
+                    profiles.default = (name != DEFAULT_PROFILE).then(|| name.clone());
+                    profiles.save(&root)?;
+
+                    output.print(format_args!(
+                        "Using profile '{}' by default",
+                        name.bright_blue()
+                    ));
+                    output.emit(Event::DefaultProfile { name });
+                }
+            },
+            CLISubcommand::Timesheet { command } => {
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+                let jira_api = JiraApi(&tokens.jira, &config);
+                let me = cached_me(&config_root, &config, &tokens, now, dry_run).await?;
+                let tempo_api = TempoApi(tokens.tempo_access_token()?, &config);
+
+                let period = match &command {
+                    TimesheetSubcommand::Status { period }
+                    | TimesheetSubcommand::Submit { period, .. } => *period,
+                };
+                let mut approval = tempo_api.get_approval(&me, now.date()).await?;
+                if period == Period::Previous {
+                    let date = approval.period.from - Duration::days(1);
+                    approval = tempo_api.get_approval(&me, date).await?;
+                }
+
+                match command {
+                    TimesheetSubcommand::Status { .. } => {
+                        show_approval(output, &jira_api, &approval).await?;
+                    }
+                    TimesheetSubcommand::Submit {
+                        reviewer,
+                        comment,
+                        force,
+                        ..
+                    } => {
+                        let timers = TempoTimers::try_read(&config_root)?.unwrap_or_default();
+                        let running = timers.running().len();
+                        if running > 0 && !force {
+                            Err(TempomatError::TimersRunning(running))?
+                        }
+
+                        let reviewer = match reviewer {
+                            Some(reviewer) => find_user(&jira_api, &reviewer).await?.account_id,
+                            None => approval
+                                .reviewer
+                                .as_ref()
+                                .map(|reviewer| reviewer.account_id.clone())
+                                .ok_or(TempomatError::MissingReviewer)?,
+                        };
+
+                        if dry_run {
+                            output.print(format_args!(
+                                "Dry run, would submit the timesheet from {} to {} to reviewer {}",
+                                approval.period.from, approval.period.to, reviewer
+                            ));
+                            break 'cmd;
+                        }
+
+                        debug!("Submitting the timesheet to {reviewer}");
+                        let approval = tempo_api
+                            .submit_timesheet(&me, &approval.period, &reviewer, comment)
+                            .await?;
+
+                        output.print("Submitted the timesheet for approval");
+                        show_approval(output, &jira_api, &approval).await?;
+                    }
+                }
+            }
+            CLISubcommand::Suggest { since, no_edit } => {
+                let since = since.resolve(now.date()).and_time(NaiveTime::MIN);
+                // An explicit --repo replaces the configured repositories
+                let repos = match config.as_ref() {
+                    Some(config) if args.repo.is_none() && !config.suggest_repos.is_empty() => {
+                        config.suggest_repos.clone()
+                    }
+                    _ => vec![repo.clone()],
+                };
+
+                let mut commits = Vec::new();
+                for repo in &repos {
+                    let repo = config::expand_home(repo);
+                    debug!("Reading commits of {}", repo.display());
+                    let repository = Repository::discover(&repo)?;
+                    let matcher = BranchMatcher::read(&config_root, config.as_ref(), &repository)?;
+                    commits.extend(git::commits_since(&repo, since)?.into_iter().map(|commit| {
+                        let issue = matcher.commit_key(&commit);
+                        (commit, issue)
+                    }));
+                }
+
+                let suggestions = suggest::suggest(commits, since);
+                if suggestions.is_empty() {
+                    output.print(format_args!(
+                        "No commits with issue keys found since {}.",
+                        since.date()
+                    ));
+                    break 'cmd;
+                }
+
+                let draft = suggest::to_draft(&suggestions);
+                let draft = if no_edit {
+                    draft
+                } else {
+                    prompt::edit(&draft)?
+                };
+                let accepted = suggest::parse_draft(&draft, workday)?;
+                if accepted.is_empty() {
+                    output.print("Nothing to submit.");
+                    break 'cmd;
+                }
+                for suggestion in &accepted {
+                    let start = time::local_instant(&Local, suggestion.start)?;
+                    time::validate_worklog(&start, suggestion.seconds, &local_now)?;
+                }
+
+                // Authenticate once instead of for every worklog
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+                for suggestion in accepted {
+                    let pending = PendingWorklog {
+                        issue: suggestion.issue.to_string(),
+                        seconds: suggestion.seconds,
+                        start: suggestion.start,
+                        description: suggestion.description,
+                        timer: None,
+                        attributes: worklog_attributes(
+                            Some(&config),
+                            &suggestion.issue,
+                            Vec::new(),
+                        ),
+                        account: None,
+                        billable: None,
+                        remaining: None,
+                    };
+                    submit_or_queue(
+                        &config_root,
+                        Some(config.clone()),
+                        Some(tokens.clone()),
+                        pending,
+                        dry_run,
+                        output,
+                    )
+                    .await?;
+                }
+            }
+            CLISubcommand::Link { branch, issue } => {
+                let work_dir = Repository::discover(&repo)?.work_dir;
+                let mut links = BranchLinks::try_read(&config_root)?.unwrap_or_default();
+                links
+                    .0
+                    .entry(work_dir.clone())
+                    .or_default()
+                    .insert(branch.clone(), issue.to_string());
+
+                if dry_run {
+                    output.print("Dry run, the branch was not linked".dimmed());
+                } else {
+                    links.save(&config_root)?;
+                    output.print(format_args!(
+                        "Linked branch '{branch}' of {} to issue '{issue}'",
+                        work_dir.display()
+                    ));
+                    output.emit(Event::BranchLinked {
+                        repo: work_dir,
+                        branch,
+                        issue: issue.to_string(),
+                    });
+                }
+            }
+            CLISubcommand::Unlink { branch } => {
+                let work_dir = Repository::discover(&repo)?.work_dir;
+                let mut links = BranchLinks::try_read(&config_root)?.unwrap_or_default();
+                let branches = links.0.entry(work_dir.clone()).or_default();
+                if branches.remove(&branch).is_none() {
+                    output.print(format_args!(
+                        "Branch '{branch}' of {} is not linked.",
+                        work_dir.display()
+                    ));
+                    break 'cmd;
+                }
+                if branches.is_empty() {
+                    links.0.remove(&work_dir);
+                }
+
+                if dry_run {
+                    output.print("Dry run, the branch was not unlinked".dimmed());
+                } else {
+                    links.save(&config_root)?;
+                    output.print(format_args!(
+                        "Unlinked branch '{branch}' of {}",
+                        work_dir.display()
+                    ));
+                    output.emit(Event::BranchUnlinked {
+                        repo: work_dir,
+                        branch,
+                    });
+                }
+            }
+            CLISubcommand::Hooks {
+                command: HooksSubcommand::Install,
+            } => {
+                let hooks_dir = git::hooks_dir(&repo)?;
+                let quote = |text: &str| format!("'{}'", text.replace('\'', r"'\''"));
+                // The hook uses this binary with the same profile and configuration root
+                let mut command = quote(&env::current_exe()?.to_string_lossy());
+                if let Some(config) = &args.config {
+                    command += &format!(" --config {}", quote(&config.to_string_lossy()));
+                }
+                if let Some(profile) = &args.profile {
+                    command += &format!(" --profile {}", quote(profile));
+                }
+
+                if dry_run {
+                    output.print(format_args!(
+                        "Dry run, would install the hook in {}",
+                        hooks_dir.display()
+                    ));
+                    break 'cmd;
+                }
+
+                let message = match hooks::install(&hooks_dir, &command)? {
+                    Installed::New => "Installed the hook",
+                    Installed::Chained => "Installed the hook, the existing hook is run first",
+                    Installed::Updated => "Updated the hook",
+                };
+                output.print(format_args!("{message} in {}", hooks_dir.display()));
+                output.emit(Event::HookInstalled {
+                    path: hooks_dir.join(hooks::HOOK_NAME),
+                });
+            }
+            CLISubcommand::Hooks {
+                command: HooksSubcommand::Uninstall,
+            } => {
+                let hooks_dir = git::hooks_dir(&repo)?;
+
+                if dry_run {
+                    output.print(format_args!(
+                        "Dry run, would remove the hook from {}",
+                        hooks_dir.display()
+                    ));
+                } else if hooks::uninstall(&hooks_dir)? {
+                    output.print(format_args!(
+                        "Removed the hook from {}",
+                        hooks_dir.display()
+                    ));
+                    output.emit(Event::HookUninstalled {
+                        path: hooks_dir.join(hooks::HOOK_NAME),
+                    });
+                } else {
+                    output.print("The hook is not installed in this repository.");
+                }
+            }
+            CLISubcommand::Hooks {
+                command: HooksSubcommand::Checkout,
+            } => {
+                let previous = branches::previous_branch_key(&config_root, config.as_ref(), &repo)?;
+                let current = branches::current_branch_key(&config_root, config.as_ref(), &repo)?;
+                if previous == current {
+                    break 'cmd;
+                }
+
+                let action = config
+                    .as_ref()
+                    .map(|config| config.checkout_action)
+                    .unwrap_or_default();
+                let mut timers = TempoTimers::try_read(&config_root)?.unwrap_or_default();
+
+                if let Some(previous) = previous {
+                    let issue = previous.to_string();
+                    let running = timers.0.get(&issue).is_some_and(Timer::is_running);
+                    let action = match action {
+                        _ if !running => None,
+                        CheckoutAction::Prompt => {
+                            let answer = prompt::prompt(&format!(
+                                "The timer for {issue} is running, (p)ause, (s)top and submit or (k)eep it? "
+                            ))?;
+                            match answer.to_lowercase().as_str() {
+                                "p" | "pause" => Some(CheckoutAction::Pause),
+                                "s" | "stop" => Some(CheckoutAction::Stop),
+                                _ => None,
+                            }
+                        }
+                        action => Some(action),
+                    };
+
+                    match (action, timers.0.remove(&issue)) {
+                        (Some(CheckoutAction::Stop), Some(timer)) => {
+                            let start = timer.start().ok_or(TempomatError::TimerInvalid)?;
+                            let til_now = timer
+                                .elapsed(now)
+                                .num_seconds()
+                                .try_into()
+                                .map_err(|_| TempomatError::NegativeTime)?;
+                            let pending = PendingWorklog {
+                                issue: issue.clone(),
+                                seconds: til_now,
+                                start,
+                                description: describe(config.as_ref(), &previous, None),
+                                timer: Some(timer),
+                                attributes: worklog_attributes(
+                                    config.as_ref(),
+                                    &previous,
+                                    Vec::new(),
+                                ),
+                                account: None,
+                                billable: None,
+                                remaining: None,
+                            };
+                            submit_or_queue(
+                                &config_root,
+                                config.take(),
+                                tokens.take(),
+                                pending,
+                                dry_run,
+                                output,
+                            )
+                            .await?;
+                        }
+                        (Some(CheckoutAction::Pause), Some(mut timer)) => {
+                            timer.pause(now);
+                            timers.0.insert(issue.clone(), timer);
+                            output.print(format_args!(
+                                "Paused timer for issue '{}'",
+                                issue.bright_blue()
+                            ));
+                            output.emit(Event::TimerPaused { issue });
+                        }
+                        (_, Some(timer)) => {
+                            timers.0.insert(issue, timer);
+                        }
+                        (_, None) => {}
+                    }
+                }
+
+                if let Some(current) = current {
+                    let issue = current.to_string();
+                    let start = action != CheckoutAction::Prompt
+                        || prompt::confirm(&format!("Start the timer for {issue}?"))?;
+
+                    // A running timer is kept as it is
+                    if let Some(started) = start
+                        .then(|| start_timer(&mut timers, &issue, now))
+                        .flatten()
+                    {
+                        show_timer_started(output, started);
+                    }
+                }
+
+                save_timers(&config_root, &timers, dry_run, output)?;
+            }
+            CLISubcommand::Cache {
+                command: CacheSubcommand::Clear,
+            } => {
+                if dry_run {
+                    output.print("Dry run, the cache was not cleared".dimmed());
+                } else if Cache::clear(&config_root)? {
+                    output.print("Cleared the cache");
+                } else {
+                    output.print("There is nothing cached.");
+                }
+                output.emit(Event::CacheCleared);
+            }
+            CLISubcommand::Completions { shell } => {
+                let shells = Shells::builtins();
+                let completer = shells
+                    .completer(shell.name())
+                    .expect("all supported shells are builtin");
+                let bin = TempomatCLI::command().get_name().to_string();
+
+                completer.write_registration(COMPLETE_ENV, &bin, &bin, &bin, &mut io::stdout())?;
+            }
+            CLISubcommand::Sync { list, discard } => {
+                let mut queue = WorklogQueue::try_read(&config_root)?.unwrap_or_default();
+                if queue.0.is_empty() {
+                    output.print("There are no queued worklogs.");
+                    break 'cmd;
+                }
+
+                if list {
+                    for (position, pending) in queue.0.iter().enumerate() {
+                        output.emit(Event::QueuedWorklog {
+                            position: position + 1,
+                            issue: pending.issue.clone(),
+                            seconds: pending.seconds,
+                            start: pending.start,
+                        });
+                        output.print(format_args!(
+                            "  {}. {} on {} for issue '{}'",
+                            position + 1,
+                            time::seconds_to_string(pending.seconds).green(),
+                            pending.start.format("%Y-%m-%d %H:%M"),
+                            pending.issue.bright_blue()
+                        ));
+                    }
+                    break 'cmd;
+                }
+
+                if let Some(position) = discard {
+                    if position == 0 || position > queue.0.len() {
+                        Err(TempomatError::InvalidQueuePosition(position))?
+                    }
+
+                    let pending = queue.0.remove(position - 1);
+                    if dry_run {
+                        output.print(format_args!(
+                            "Dry run, would discard worklog of {} for issue '{}'",
+                            time::seconds_to_string(pending.seconds).green(),
+                            pending.issue.bright_blue()
+                        ));
+                        break 'cmd;
+                    }
+
+                    queue.save(&config_root)?;
+                    output.print(format_args!(
+                        "Discarded worklog of {} for issue '{}'",
+                        time::seconds_to_string(pending.seconds).green(),
+                        pending.issue.bright_blue()
+                    ));
+                    output.emit(Event::WorklogDiscarded {
+                        issue: pending.issue,
+                        seconds: pending.seconds,
+                        start: pending.start,
+                    });
+                    break 'cmd;
+                }
+
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+
+                if dry_run {
+                    for pending in &queue.0 {
+                        let (payload, jira_issue) =
+                            pending.preview(&config_root, &config, &tokens).await?;
+                        output.print(format_args!(
+                            "Dry run, would log {} for issue '{}' with:",
+                            time::seconds_to_string(pending.seconds).green(),
+                            jira_issue.fields.summary.bright_blue()
+                        ));
+                        output.print(serde_json::to_string_pretty(&payload)?);
+                        output.emit(Event::WorklogPreview {
+                            issue: pending.issue.clone(),
+                            payload,
+                        });
+                    }
+                    break 'cmd;
+                }
+
+                let mut failed = Vec::new();
+                let total = queue.0.len();
+
+                for pending in queue.0.drain(..) {
+                    let time_text = time::seconds_to_string(pending.seconds);
+
+                    match pending.submit(&config_root, &config, &tokens).await {
+                        Ok((Some(worklog), _)) => {
+                            output.print(format_args!(
+                                "  {} {} on {} for issue '{}' (worklog {})",
+                                "✓".green(),
+                                time_text.green(),
+                                pending.start.format("%Y-%m-%d %H:%M"),
+                                pending.issue.bright_blue(),
+                                worklog.id.to_string().bright_yellow()
+                            ));
+                            output.emit(Event::WorklogCreated {
+                                id: worklog.id,
+                                issue: pending.issue.clone(),
+                                seconds: worklog.time_spent,
+                                start: worklog.start(),
+                            });
+                            record_submission(
+                                &config_root,
+                                &worklog,
+                                &pending.issue,
+                                pending.timer,
+                            )?;
+                        }
+                        result => {
+                            let error = match result {
+                                Err(error) => error.to_string(),
+                                _ => "the worklog was rejected, check logs".to_string(),
+                            };
+                            output.print(format_args!(
+                                "  {} {} on {} for issue '{}': {error}",
+                                "✗".red(),
+                                time_text,
+                                pending.start.format("%Y-%m-%d %H:%M"),
+                                pending.issue.bright_blue()
+                            ));
+                            output.emit(Event::WorklogQueued {
+                                issue: pending.issue.clone(),
+                                seconds: pending.seconds,
+                                start: pending.start,
+                                error,
+                            });
+                            failed.push(pending);
+                        }
+                    }
+                }
+
+                let submitted = total - failed.len();
+                queue.0 = failed;
+                queue.save(&config_root)?;
+
+                if queue.0.is_empty() {
+                    output.print(format_args!("Submitted all {submitted} queued worklogs"));
+                } else {
+                    output.print(format_args!(
+                        "Submitted {submitted} of {total} queued worklogs, {} still queued",
+                        queue.0.len().to_string().red()
+                    ));
+                }
+            }
+            CLISubcommand::Undo => {
+                let mut journal = Journal::try_read(&config_root)?.unwrap_or_default();
+                let Some(entry) = journal.last().cloned() else {
+                    output.print("There is nothing to undo.");
+                    break 'cmd;
+                };
+
+                let (config, tokens) = authenticate(config.take(), tokens.take()).await?;
+
+                if dry_run {
+                    output.print(format_args!(
+                        "Dry run, would delete worklog {} of {} for issue '{}'",
+                        entry.worklog_id.to_string().bright_yellow(),
+                        time::seconds_to_string(entry.seconds).green(),
+                        entry.issue.bright_blue()
+                    ));
+                    break 'cmd;
+                }
+
+                debug!("Deleting worklog {}", entry.worklog_id);
+                if !TempoApi(tokens.tempo_access_token()?, &config)
+                    .delete_worklog(entry.worklog_id)
+                    .await?
+                {
+                    Err(TempomatError::WorklogRejected(entry.worklog_id))?
+                }
+
+                journal.0.pop();
+                journal.save(&config_root)?;
+                output.emit(Event::WorklogDeleted {
+                    id: entry.worklog_id,
+                });
+
+                output.print(format_args!(
+                    "Deleted worklog {} of {} for issue '{}'",
+                    entry.worklog_id.to_string().bright_yellow(),
+                    time::seconds_to_string(entry.seconds).green(),
+                    entry.issue.bright_blue()
+                ));
+
+                if let Some(timer) = entry.timer {
+                    let mut timers = TempoTimers::try_read(&config_root)?.unwrap_or_default();
+
+                    if timers.0.contains_key(&entry.issue) {
+                        output.print(format_args!(
+                            "A timer for issue '{}' is already running, not restoring the old one",
+                            entry.issue.bright_blue()
+                        ));
+                    } else {
+                        let start = timer.start().unwrap_or(entry.start);
+                        timers.0.insert(entry.issue.clone(), timer);
+                        save_timers(&config_root, &timers, dry_run, output)?;
+                        output.emit(Event::TimerRestored {
+                            issue: entry.issue.clone(),
+                            start,
+                        });
+
+                        output.print(format_args!(
+                            "Restored timer started on {} for issue '{}'",
+                            start.to_string().bright_yellow(),
+                            entry.issue.bright_blue()
+                        ));
                     }
                 }
             }
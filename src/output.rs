@@ -0,0 +1,243 @@
+use crate::{error::TempomatError, tempo::types::ApprovalStatus};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{cell::RefCell, fmt::Display, path::PathBuf};
+
+/// How the results of a command are printed
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Colored text
+    #[default]
+    Human,
+    /// Text without colors
+    Plain,
+    /// A single JSON document with the results, or the error
+    Json,
+}
+
+/// A structured result of a command, only printed with `--output json`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    WorklogCreated {
+        id: usize,
+        issue: String,
+        seconds: usize,
+        start: NaiveDateTime,
+    },
+    /// A worklog that could not be submitted and is kept for `sync`
+    WorklogQueued {
+        issue: String,
+        seconds: usize,
+        start: NaiveDateTime,
+        error: String,
+    },
+    /// The payload a worklog would be sent with, on dry runs
+    WorklogPreview {
+        issue: String,
+        payload: serde_json::Value,
+    },
+    WorklogUpdated {
+        id: usize,
+        seconds: usize,
+        start: NaiveDateTime,
+    },
+    WorklogDeleted {
+        id: usize,
+    },
+    WorklogDiscarded {
+        issue: String,
+        seconds: usize,
+        start: NaiveDateTime,
+    },
+    Worklog {
+        id: usize,
+        issue: String,
+        summary: String,
+        seconds: usize,
+        start: NaiveDateTime,
+        description: Option<String>,
+    },
+    Issue {
+        key: String,
+        summary: String,
+    },
+    /// A day of `report`, the balance includes the running time
+    ReportDay {
+        date: NaiveDate,
+        logged_seconds: usize,
+        running_seconds: usize,
+        required_seconds: usize,
+        balance_seconds: i64,
+    },
+    /// A worklog waiting in the queue, positions start at 1
+    QueuedWorklog {
+        position: usize,
+        issue: String,
+        seconds: usize,
+        start: NaiveDateTime,
+    },
+    Timer {
+        issue: String,
+        running: bool,
+        elapsed_seconds: i64,
+        start: Option<NaiveDateTime>,
+    },
+    TimerStarted {
+        issue: String,
+        start: NaiveDateTime,
+    },
+    TimerCancelled {
+        issue: String,
+    },
+    TimerPaused {
+        issue: String,
+    },
+    TimerResumed {
+        issue: String,
+    },
+    TimerRestored {
+        issue: String,
+        start: NaiveDateTime,
+    },
+    Timesheet {
+        from: NaiveDate,
+        to: NaiveDate,
+        status: ApprovalStatus,
+        reviewer: Option<String>,
+        logged_seconds: usize,
+        required_seconds: usize,
+    },
+    LoggedIn {
+        jira_url: String,
+    },
+    Profile {
+        name: String,
+        default: bool,
+    },
+    ProfileAdded {
+        name: String,
+    },
+    ProfileRemoved {
+        name: String,
+    },
+    DefaultProfile {
+        name: String,
+    },
+    HookInstalled {
+        path: PathBuf,
+    },
+    HookUninstalled {
+        path: PathBuf,
+    },
+    CacheCleared,
+    BranchLinked {
+        repo: PathBuf,
+        branch: String,
+        issue: String,
+    },
+    BranchUnlinked {
+        repo: PathBuf,
+        branch: String,
+    },
+}
+
+#[derive(Serialize)]
+struct ErrorOutput {
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    results: &'a [Event],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorOutput>,
+}
+
+/// Prints text for humans or collects events to print them as JSON when the command is done
+pub struct Output {
+    format: OutputFormat,
+    events: RefCell<Vec<Event>>,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Self {
+        if format != OutputFormat::Human {
+            colored::control::set_override(false);
+        }
+
+        Self {
+            format,
+            events: RefCell::default(),
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Prints a line of text, unless the output is JSON
+    pub fn print(&self, text: impl Display) {
+        if !self.is_json() {
+            println!("{text}");
+        }
+    }
+
+    /// Records a result, only printed if the output is JSON
+    pub fn emit(&self, event: Event) {
+        if self.is_json() {
+            self.events.borrow_mut().push(event);
+        }
+    }
+
+    /// Prints the collected results and the error of the command as JSON
+    pub fn finish(&self, error: Option<&TempomatError>) -> Result<(), TempomatError> {
+        if !self.is_json() {
+            return Ok(());
+        }
+
+        let output = JsonOutput {
+            results: &self.events.borrow(),
+            error: error.map(|error| ErrorOutput {
+                code: error.code(),
+                message: error.to_string(),
+            }),
+        };
+        println!("{}", serde_json::to_string(&output)?);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ErrorOutput, Event, JsonOutput};
+    use crate::error::TempomatError;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_json_output() {
+        let start = NaiveDate::from_ymd_opt(2023, 4, 10)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let error = TempomatError::TimerInvalid;
+        let output = JsonOutput {
+            results: &[Event::TimerStarted {
+                issue: "DV-1".to_string(),
+                start,
+            }],
+            error: Some(ErrorOutput {
+                code: error.code(),
+                message: error.to_string(),
+            }),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            r#"{"results":[{"type":"timer_started","issue":"DV-1","start":"2023-04-10T09:00:00"}],"error":{"code":"timer_invalid","message":"Timer does not exist"}}"#
+        );
+    }
+}
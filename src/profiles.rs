@@ -0,0 +1,87 @@
+use crate::{config::Saveable, error::TempomatError};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the profile stored directly in the configuration root
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIRECTORY: &str = "profiles";
+
+/// Profile settings, stored in the configuration root
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Profiles {
+    /// Profile used when none is given, the default profile if not set
+    pub default: Option<String>,
+}
+
+impl Profiles {
+    /// Name of the profile used when none is given
+    pub fn default_profile(&self) -> &str {
+        self.default.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+}
+
+impl Saveable for Profiles {
+    fn path(root: &Path) -> PathBuf {
+        root.join("profiles.ron")
+    }
+}
+
+/// Gets the directory where the configuration, tokens and timers of a profile are stored
+pub fn profile_root(root: &Path, name: &str) -> Result<PathBuf, TempomatError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid {
+        Err(TempomatError::InvalidProfileName(name.to_string()))?
+    }
+
+    if name == DEFAULT_PROFILE {
+        Ok(root.to_owned())
+    } else {
+        Ok(root.join(PROFILES_DIRECTORY).join(name))
+    }
+}
+
+/// Lists the names of all existing profiles, starting with the default profile
+pub fn list(root: &Path) -> Result<Vec<String>, TempomatError> {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+    let Ok(entries) = fs::read_dir(root.join(PROFILES_DIRECTORY)) else {
+        return Ok(profiles);
+    };
+
+    let mut named = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            named.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    named.sort();
+    profiles.extend(named);
+
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::profile_root;
+    use std::path::Path;
+
+    #[test]
+    fn test_profile_root() {
+        let root = Path::new("/config");
+
+        assert_eq!(profile_root(root, "default").unwrap(), root);
+        assert_eq!(
+            profile_root(root, "client-a").unwrap(),
+            root.join("profiles").join("client-a")
+        );
+        assert!(profile_root(root, "../escape").is_err());
+        assert!(profile_root(root, "").is_err());
+    }
+}
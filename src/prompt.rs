@@ -0,0 +1,69 @@
+use crate::{error::TempomatError, jira::types::Issue};
+use dialoguer::FuzzySelect;
+use std::{
+    env, fs,
+    io::{self, Write},
+    process::{self, Command},
+};
+
+/// Asks the user for a line of input
+pub fn prompt(prompt: &str) -> Result<String, TempomatError> {
+    let mut response = String::new();
+
+    {
+        // Prompts go to stderr to keep stdout parseable
+        let mut stderr = io::stderr().lock();
+        let _ = stderr.write(prompt.as_bytes());
+        let _ = stderr.flush();
+    }
+
+    io::stdin().read_line(&mut response)?;
+    Ok(response.trim().to_string())
+}
+
+/// Asks the user a yes/no question, defaults to no
+pub fn confirm(question: &str) -> Result<bool, TempomatError> {
+    let response = prompt(&format!("{question} [y/N] "))?;
+
+    Ok(matches!(response.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Lets the user fuzzy search the issues by their key and summary, None if nothing was picked
+pub fn pick_issue(issues: &[Issue]) -> Result<Option<&Issue>, TempomatError> {
+    let items: Vec<String> = issues
+        .iter()
+        .map(|issue| format!("{}  {}", issue.key, issue.fields.summary))
+        .collect();
+    let picked = FuzzySelect::new()
+        .with_prompt("Issue")
+        .items(&items)
+        .max_length(15)
+        .interact_opt()
+        .map_err(|dialoguer::Error::IO(error)| error)?;
+
+    Ok(picked.map(|index| &issues[index]))
+}
+
+/// Lets the user edit the text in $VISUAL or $EDITOR (vi if neither is set), returns the result
+pub fn edit(text: &str) -> Result<String, TempomatError> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let path = env::temp_dir().join(format!("tempomat-{}.txt", process::id()));
+    fs::write(&path, text)?;
+
+    // The editor may come with arguments, eg. `code --wait`
+    let mut words = editor.split_whitespace();
+    let status = Command::new(words.next().unwrap_or("vi"))
+        .args(words)
+        .arg(&path)
+        .status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    if !status?.success() {
+        Err(TempomatError::EditorFailed(editor))?
+    }
+
+    Ok(edited?)
+}
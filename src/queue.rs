@@ -0,0 +1,263 @@
+use crate::{
+    attributes,
+    cache::Cache,
+    config::{APITokens, Config, Saveable},
+    error::TempomatError,
+    jira::{
+        api::JiraApi,
+        types::{Issue, JiraIssueKey, Myself},
+    },
+    tempo::{
+        api::TempoApi,
+        types::{AttributeValue, Worklog, WorklogTime},
+    },
+    timers::Timer,
+};
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// A worklog that still has to be submitted to Tempo
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingWorklog {
+    pub issue: String,
+    pub seconds: usize,
+    pub start: NaiveDateTime,
+    pub description: Option<String>,
+    /// The timer this worklog comes from, if any
+    pub timer: Option<Timer>,
+    /// Work attribute values by the key or name of the attribute, a later value of an attribute
+    /// overrides an earlier one
+    #[serde(default)]
+    pub attributes: Vec<(String, String)>,
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(default)]
+    pub billable: Option<usize>,
+    #[serde(default)]
+    pub remaining: Option<RemainingEstimate>,
+}
+
+/// Remaining estimate of the issue after the worklog is submitted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RemainingEstimate {
+    /// The current remaining estimate minus the logged time
+    Auto,
+    Seconds(usize),
+}
+
+impl PendingWorklog {
+    /// Submits the worklog, returns the created worklog if successful and the Jira issue
+    pub async fn submit(
+        &self,
+        root: &Path,
+        config: &Config,
+        tokens: &APITokens,
+    ) -> Result<(Option<Worklog>, Issue), TempomatError> {
+        let mut cache = Cache::read(root, &config.jira_url()?, &tokens.jira.email)?;
+        let (jira_issue, me, attributes) = self.resolve(&mut cache, config, tokens).await?;
+        cache.save(root)?;
+
+        debug!("Submitting the worklog");
+        let worklog = TempoApi(tokens.tempo_access_token()?, config)
+            .create_worklog(
+                &me,
+                &jira_issue.id,
+                self.description.clone(),
+                self.time(&jira_issue),
+                &attributes,
+            )
+            .await?;
+
+        Ok((worklog, jira_issue))
+    }
+
+    /// Resolves everything needed to submit the worklog, returns the payload that would be sent
+    /// and the Jira issue. Nothing is saved, not even the lookups
+    pub async fn preview(
+        &self,
+        root: &Path,
+        config: &Config,
+        tokens: &APITokens,
+    ) -> Result<(serde_json::Value, Issue), TempomatError> {
+        let mut cache = Cache::read(root, &config.jira_url()?, &tokens.jira.email)?;
+        let (jira_issue, me, attributes) = self.resolve(&mut cache, config, tokens).await?;
+        let payload = TempoApi(tokens.tempo_access_token()?, config).preview_payload(
+            &me.account_id,
+            &jira_issue.id,
+            self.time(&jira_issue),
+            self.description.clone(),
+            &attributes,
+        )?;
+
+        Ok((payload, jira_issue))
+    }
+
+    /// The time to submit, the automatic remaining estimate is based on the estimate of `issue`
+    pub fn time(&self, issue: &Issue) -> WorklogTime {
+        let remaining = self.remaining.map(|remaining| match remaining {
+            RemainingEstimate::Auto => issue
+                .fields
+                .timetracking
+                .remaining_estimate
+                .unwrap_or(0)
+                .saturating_sub(self.seconds),
+            RemainingEstimate::Seconds(seconds) => seconds,
+        });
+
+        WorklogTime {
+            start: self.start,
+            time_spent: self.seconds,
+            billable: self.billable,
+            remaining,
+        }
+    }
+
+    async fn resolve(
+        &self,
+        cache: &mut Cache,
+        config: &Config,
+        tokens: &APITokens,
+    ) -> Result<(Issue, Myself, Vec<AttributeValue>), TempomatError> {
+        let issue_key: JiraIssueKey = self.issue.parse()?;
+        let jira_api = JiraApi(&tokens.jira, config);
+        let tempo_api = TempoApi(tokens.tempo_access_token()?, config);
+        // The cached issue has no estimate
+        let fresh_issue = self.remaining == Some(RemainingEstimate::Auto);
+        // Work attributes are only needed to match the given or configured values
+        let uses_attributes = !self.attributes.is_empty() || self.account.is_some();
+
+        debug!("Getting issue key, user information and work attributes...");
+        let (jira_issue, me, work_attributes) = cache
+            .worklog_lookups(
+                &jira_api,
+                uses_attributes.then_some(&tempo_api),
+                &issue_key,
+                fresh_issue,
+                Local::now().naive_local(),
+                config.cache_ttl(),
+            )
+            .await?;
+
+        // Validate the attributes before submitting, Tempo would only reject the worklog
+        let attributes = match work_attributes {
+            Some(work_attributes) => {
+                attributes::resolve(&work_attributes, &self.attributes, self.account.as_deref())?
+            }
+            None => Vec::new(),
+        };
+
+        Ok((jira_issue, me, attributes))
+    }
+}
+
+/// Worklogs that could not be submitted yet, oldest first
+#[derive(Serialize, Deserialize, Default)]
+pub struct WorklogQueue(pub Vec<PendingWorklog>);
+
+impl WorklogQueue {
+    /// Amount of queued worklogs, 0 if there is no queue
+    pub fn pending(root: &Path) -> Result<usize, TempomatError> {
+        Ok(Self::try_read(root)?.map_or(0, |queue| queue.0.len()))
+    }
+}
+
+impl Saveable for WorklogQueue {
+    fn path(root: &Path) -> PathBuf {
+        root.join("queue.ron")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PendingWorklog, RemainingEstimate, WorklogQueue};
+    use crate::{
+        config::ron_options,
+        jira::types::{Issue, IssueFields, TimeTracking},
+        timers::Timer,
+    };
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_queue_roundtrip() {
+        let start = NaiveDate::from_ymd_opt(2023, 4, 10)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let queue = WorklogQueue(vec![
+            PendingWorklog {
+                issue: "DV-1".to_string(),
+                seconds: 3600,
+                start,
+                description: Some("Review".to_string()),
+                timer: Some(Timer::new(start)),
+                attributes: vec![
+                    ("account".to_string(), "ACC-1".to_string()),
+                    ("Account".to_string(), "ACC-2".to_string()),
+                ],
+                account: None,
+                billable: Some(1800),
+                remaining: Some(RemainingEstimate::Auto),
+            },
+            PendingWorklog {
+                issue: "DV-2".to_string(),
+                seconds: 60,
+                start,
+                description: None,
+                timer: None,
+                attributes: Default::default(),
+                account: None,
+                billable: None,
+                remaining: None,
+            },
+        ]);
+
+        let saved = ron_options().to_string(&queue).unwrap();
+        let queue: WorklogQueue = ron_options().from_str(&saved).unwrap();
+
+        assert_eq!(queue.0.len(), 2);
+        assert_eq!(queue.0[0].issue, "DV-1");
+        assert_eq!(queue.0[0].timer, Some(Timer::new(start)));
+        assert_eq!(queue.0[0].attributes[1].1, "ACC-2");
+        assert_eq!(queue.0[0].remaining, Some(RemainingEstimate::Auto));
+        assert_eq!(queue.0[1].description, None);
+        assert_eq!(queue.0[1].billable, None);
+    }
+
+    #[test]
+    fn test_remaining_estimate() {
+        let start = NaiveDate::from_ymd_opt(2023, 4, 10)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let issue = |remaining_estimate| Issue {
+            id: "10000".to_string(),
+            key: "DV-1".to_string(),
+            fields: IssueFields {
+                summary: "Review".to_string(),
+                timetracking: TimeTracking { remaining_estimate },
+            },
+        };
+        let pending = |remaining| PendingWorklog {
+            issue: "DV-1".to_string(),
+            seconds: 3600,
+            start,
+            description: None,
+            timer: None,
+            attributes: Default::default(),
+            account: None,
+            billable: None,
+            remaining,
+        };
+
+        let auto = pending(Some(RemainingEstimate::Auto));
+        assert_eq!(auto.time(&issue(Some(5 * 3600))).remaining, Some(4 * 3600));
+        assert_eq!(auto.time(&issue(Some(1800))).remaining, Some(0));
+        assert_eq!(auto.time(&issue(None)).remaining, Some(0));
+
+        let fixed = pending(Some(RemainingEstimate::Seconds(7200)));
+        assert_eq!(fixed.time(&issue(Some(5 * 3600))).remaining, Some(7200));
+        assert_eq!(pending(None).time(&issue(Some(5 * 3600))).remaining, None);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    tempo::types::{ScheduleDay, Worklog},
+    timers::TempoTimers,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+/// Logged and required time of a single day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayReport {
+    pub date: NaiveDate,
+    /// Seconds logged in Tempo
+    pub logged: usize,
+    /// Seconds in timers that started on this day, they will be logged on it once stopped
+    pub running: usize,
+    pub required: usize,
+}
+
+impl DayReport {
+    /// Logged and running time minus the required time
+    pub fn balance(&self) -> i64 {
+        (self.logged + self.running) as i64 - self.required as i64
+    }
+}
+
+/// Reports every day between `from` and `to` (both inclusive)
+pub fn daily_report(
+    from: NaiveDate,
+    to: NaiveDate,
+    worklogs: &[Worklog],
+    schedule: &[ScheduleDay],
+    timers: &TempoTimers,
+    now: NaiveDateTime,
+) -> Vec<DayReport> {
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| DayReport {
+            date,
+            logged: worklogs
+                .iter()
+                .filter(|worklog| worklog.start_date == date)
+                .map(|worklog| worklog.time_spent)
+                .sum(),
+            running: timers
+                .0
+                .values()
+                .filter(|timer| timer.start().is_some_and(|start| start.date() == date))
+                .map(|timer| timer.elapsed(now).max(Duration::zero()).num_seconds() as usize)
+                .sum(),
+            required: schedule
+                .iter()
+                .filter(|day| day.date == date)
+                .map(|day| day.required_seconds)
+                .sum(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::daily_report;
+    use crate::{
+        tempo::types::{ScheduleDay, Worklog, WorklogAuthor, WorklogIssue},
+        timers::{TempoTimers, Timer},
+    };
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_daily_report() {
+        let monday = NaiveDate::from_ymd_opt(2023, 4, 10).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+        let worklog = |date: NaiveDate, time_spent| Worklog {
+            id: 1,
+            issue: WorklogIssue { id: 10000 },
+            time_spent,
+            billable: None,
+            start_date: date,
+            start_time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            description: None,
+            author: WorklogAuthor {
+                account_id: "me".to_string(),
+            },
+            attributes: Default::default(),
+        };
+        let schedule = [monday, tuesday].map(|date| ScheduleDay {
+            date,
+            required_seconds: 8 * 3600,
+        });
+        let timers = TempoTimers(
+            [(
+                "DV-1".to_string(),
+                Timer::new(tuesday.and_hms_opt(9, 0, 0).unwrap()),
+            )]
+            .into(),
+        );
+
+        let report = daily_report(
+            monday,
+            tuesday,
+            &[worklog(monday, 6 * 3600), worklog(monday, 3 * 3600)],
+            &schedule,
+            &timers,
+            tuesday.and_hms_opt(11, 0, 0).unwrap(),
+        );
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].logged, 9 * 3600);
+        assert_eq!(report[0].running, 0);
+        assert_eq!(report[0].balance(), 3600);
+        assert_eq!(report[1].logged, 0);
+        assert_eq!(report[1].running, 2 * 3600);
+        assert_eq!(report[1].balance(), -6 * 3600);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    args::parsers::parse_arg, error::TempomatError, git::Commit, jira::types::JiraIssueKey, time,
+};
+use chrono::{Duration, NaiveDateTime};
+
+/// Commits further apart than this are not assumed to be worked on in between
+const MAX_GAP_MINUTES: i64 = 120;
+
+/// Time assumed to be spent before a commit that doesn't follow another one closely
+const FIRST_COMMIT_MINUTES: i64 = 30;
+
+/// A worklog reconstructed from commits, or read back from the edited draft
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub issue: JiraIssueKey,
+    pub start: NaiveDateTime,
+    pub seconds: usize,
+    pub description: Option<String>,
+}
+
+/// Estimates worklogs from commits and their issues, the time since the previous commit (of any
+/// issue) counts for the issue of a commit
+pub fn suggest(
+    mut commits: Vec<(Commit, Option<JiraIssueKey>)>,
+    since: NaiveDateTime,
+) -> Vec<Suggestion> {
+    commits.sort_by_key(|(commit, _)| commit.time);
+
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut previous: Option<NaiveDateTime> = None;
+
+    for (commit, issue) in commits {
+        let start = match previous {
+            Some(previous)
+                if previous.date() == commit.time.date()
+                    && commit.time - previous <= Duration::minutes(MAX_GAP_MINUTES) =>
+            {
+                previous
+            }
+            _ => (commit.time - Duration::minutes(FIRST_COMMIT_MINUTES)).max(since),
+        };
+        previous = Some(commit.time);
+
+        let Some(issue) = issue else {
+            continue;
+        };
+        let seconds = (commit.time - start).num_seconds().max(0) as usize;
+
+        // Consecutive commits of the same issue make one worklog
+        match suggestions.last_mut() {
+            Some(last)
+                if last.issue == issue
+                    && last.start + Duration::seconds(last.seconds as i64) == start =>
+            {
+                last.seconds += seconds;
+                if let Some(description) = &mut last.description {
+                    *description += "; ";
+                    *description += &commit.message;
+                }
+            }
+            _ if seconds > 0 => suggestions.push(Suggestion {
+                issue,
+                start,
+                seconds,
+                description: Some(commit.message),
+            }),
+            _ => {}
+        }
+    }
+
+    suggestions
+}
+
+/// The suggestions as text to edit, one worklog per line
+pub fn to_draft(suggestions: &[Suggestion]) -> String {
+    let mut draft = String::from(
+        "# Worklogs reconstructed from your commits, they are submitted once you save and quit.\n\
+         # Change the issue, date, start time, duration (without spaces) or description of a\n\
+         # line, or remove it to not submit it.\n\
+         #\n\
+         # ISSUE DATE START DURATION DESCRIPTION\n",
+    );
+
+    for suggestion in suggestions {
+        let line = format!(
+            "{} {} {}",
+            suggestion.issue,
+            suggestion.start.format("%Y-%m-%d %H:%M"),
+            time::seconds_to_string(suggestion.seconds),
+        );
+
+        match &suggestion.description {
+            Some(description) => draft += &format!("{line} {description}\n"),
+            None => draft += &format!("{line}\n"),
+        }
+    }
+
+    draft
+}
+
+/// Reads the worklogs from an edited draft, `workday` is the amount of seconds in a workday
+pub fn parse_draft(draft: &str, workday: usize) -> Result<Vec<Suggestion>, TempomatError> {
+    draft
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(number, line)| {
+            let invalid = || TempomatError::InvalidDraftLine(number + 1, line.to_string());
+            let mut fields = line.split_whitespace();
+            let (Some(issue), Some(date), Some(start), Some(duration)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                Err(invalid())?
+            };
+            let description = fields.collect::<Vec<_>>().join(" ");
+
+            Ok(Suggestion {
+                issue: issue.parse().map_err(|_| invalid())?,
+                start: NaiveDateTime::parse_from_str(&format!("{date} {start}"), "%Y-%m-%d %H:%M")
+                    .map_err(|_| invalid())?,
+                seconds: parse_arg(duration).map_err(|_| invalid())?.seconds(workday),
+                description: (!description.is_empty()).then_some(description),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_draft, suggest, to_draft, Suggestion};
+    use crate::{branches::BranchMatcher, git::Commit, jira::types::JiraIssueKey};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 4, 10)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn commit(time: NaiveDateTime, branch: &str, message: &str) -> (Commit, Option<JiraIssueKey>) {
+        let commit = Commit {
+            time,
+            branch: branch.to_string(),
+            message: message.to_string(),
+        };
+        let matcher = BranchMatcher::new(None, None, Default::default()).unwrap();
+        let issue = matcher.commit_key(&commit);
+
+        (commit, issue)
+    }
+
+    #[test]
+    fn test_suggest() {
+        let suggestions = suggest(
+            vec![
+                commit(at(16, 0), "refs/heads/main", "Release DV-2"),
+                commit(at(9, 20), "refs/heads/feat/DV-1", "Fix login"),
+                commit(at(10, 0), "refs/heads/feat/DV-1", "Add tests"),
+                commit(at(11, 0), "refs/heads/main", "Update dependencies"),
+                commit(at(12, 30), "refs/heads/main", "DV-2: Write docs"),
+            ],
+            at(9, 0),
+        );
+
+        assert_eq!(
+            suggestions,
+            [
+                Suggestion {
+                    issue: "DV-1".parse().unwrap(),
+                    start: at(9, 0),
+                    seconds: 60 * 60,
+                    description: Some("Fix login; Add tests".to_string()),
+                },
+                Suggestion {
+                    issue: "DV-2".parse().unwrap(),
+                    start: at(11, 0),
+                    seconds: 90 * 60,
+                    description: Some("DV-2: Write docs".to_string()),
+                },
+                // Too long after the previous commit
+                Suggestion {
+                    issue: "DV-2".parse().unwrap(),
+                    start: at(15, 30),
+                    seconds: 30 * 60,
+                    description: Some("Release DV-2".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draft_roundtrip() {
+        let suggestions = vec![
+            Suggestion {
+                issue: "DV-1".parse().unwrap(),
+                start: at(9, 0),
+                seconds: 90 * 60,
+                description: Some("Fix login; Add tests".to_string()),
+            },
+            Suggestion {
+                issue: "DV-2".parse().unwrap(),
+                start: at(11, 0),
+                seconds: 60,
+                description: None,
+            },
+        ];
+        let draft = to_draft(&suggestions);
+
+        assert_eq!(parse_draft(&draft, 8 * 3600).unwrap(), suggestions);
+        assert_eq!(
+            parse_draft(
+                "DV-3 2023-04-10 13:00 1d\n# DV-4 2023-04-10 14:00 1h",
+                6 * 3600
+            )
+            .unwrap(),
+            [Suggestion {
+                issue: "DV-3".parse().unwrap(),
+                start: at(13, 0),
+                seconds: 6 * 3600,
+                description: None,
+            }]
+        );
+        assert!(parse_draft("DV-3 2023-04-10 1h", 8 * 3600).is_err());
+        assert!(parse_draft("DV-3 2023-04-10 13:00 1x", 8 * 3600).is_err());
+    }
+}
@@ -1,50 +1,163 @@
-use super::oauth::TempoAccessTokens;
-use crate::{error::TempomatError, jira::types::Myself};
-use chrono::{NaiveDate, NaiveDateTime};
-use reqwest::Client;
+use super::types::{
+    ApprovalPeriod, AttributeValue, Page, Results, ScheduleDay, ServerApproval, ServerSchedule,
+    ServerWorkAttribute, ServerWorklog, TimesheetApproval, WorkAttribute, Worklog, WorklogTime,
+};
+use crate::{
+    config::{Config, Deployment},
+    error::TempomatError,
+    http_client,
+    jira::types::Myself,
+};
+use chrono::NaiveDate;
+use reqwest::{Method, RequestBuilder, Response};
 use serde::Serialize;
-use tracing::{error, instrument};
+use std::collections::HashMap;
+use tracing::{debug, error, instrument};
 
+/// Amount of worklogs requested per page when searching
+const PAGE_LIMIT: usize = 50;
+
+/// Tempo API client, authenticated with an OAuth access token or an API token
 #[derive(Debug)]
-pub struct TempoApi<'a>(pub &'a TempoAccessTokens);
+pub struct TempoApi<'a>(pub &'a str, pub &'a Config);
+
+/// Worklog payload for the Tempo Cloud API
+#[derive(Serialize, Debug)]
+struct CloudWorklogPayload<'a> {
+    #[serde(rename = "authorAccountId")]
+    author_account_id: &'a str,
+    #[serde(rename = "issueId")]
+    issue_id: &'a str,
+    #[serde(rename = "startDate")]
+    start_date: NaiveDate,
+    #[serde(rename = "startTime")]
+    start_time: String,
+    #[serde(rename = "timeSpentSeconds")]
+    time_spent: usize,
+    #[serde(rename = "billableSeconds", skip_serializing_if = "Option::is_none")]
+    billable: Option<usize>,
+    #[serde(
+        rename = "remainingEstimateSeconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    remaining: Option<usize>,
+    description: Option<String>,
+    attributes: &'a [AttributeValue],
+}
+
+/// Worklog payload for Tempo Timesheets on Jira Server
+#[derive(Serialize, Debug)]
+struct ServerWorklogPayload<'a> {
+    worker: &'a str,
+    #[serde(rename = "originTaskId")]
+    origin_task_id: &'a str,
+    started: String,
+    #[serde(rename = "timeSpentSeconds")]
+    time_spent: usize,
+    #[serde(rename = "billableSeconds", skip_serializing_if = "Option::is_none")]
+    billable: Option<usize>,
+    #[serde(rename = "remainingEstimate", skip_serializing_if = "Option::is_none")]
+    remaining: Option<usize>,
+    comment: Option<String>,
+    attributes: HashMap<&'a str, ServerAttributePayload<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+struct ServerAttributePayload<'a> {
+    #[serde(rename = "workAttributeId")]
+    work_attribute_id: Option<usize>,
+    value: &'a str,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum WorklogPayload<'a> {
+    Cloud(CloudWorklogPayload<'a>),
+    Server(ServerWorklogPayload<'a>),
+}
 
 impl<'a> TempoApi<'a> {
+    /// Creates a new worklog, returns the created worklog if successful
     #[instrument(level = "trace")]
     pub async fn create_worklog(
         &self,
         me: &Myself,
         issue_id: &str,
         description: Option<String>,
-        time_spent: usize,
-        start: NaiveDateTime,
-    ) -> Result<bool, TempomatError> {
-        #[derive(Serialize)]
-        struct Payload<'a> {
-            #[serde(rename = "authorAccountId")]
-            author_account_id: &'a str,
-            #[serde(rename = "issueId")]
-            issue_id: &'a str,
-            #[serde(rename = "startDate")]
-            start_date: NaiveDate,
-            #[serde(rename = "startTime")]
-            start_time: String,
-            #[serde(rename = "timeSpentSeconds")]
-            time_spent: usize,
-            description: Option<String>,
-        }
-
-        let client = Client::new();
-        let response = client
-            .post("https://api.tempo.io/4/worklogs")
-            .bearer_auth(&self.0.access_token)
-            .json(&Payload {
-                author_account_id: &me.account_id,
-                issue_id,
-                time_spent,
-                start_date: start.date(),
-                start_time: start.time().format("%H:%M:%S").to_string(),
-                description,
-            })
+        time: WorklogTime,
+        attributes: &[AttributeValue],
+    ) -> Result<Option<Worklog>, TempomatError> {
+        let response = self
+            .request(Method::POST, "worklogs")?
+            .json(&self.payload(&me.account_id, issue_id, time, description, attributes))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            error!(
+                "Got error when creating worklog: {}",
+                response.text().await?
+            );
+            return Ok(None);
+        }
+
+        match self.1.deployment {
+            Deployment::Cloud => Ok(Some(response.json().await?)),
+            // Tempo Server returns a list of the created worklogs
+            Deployment::Server => Ok(response
+                .json::<Vec<ServerWorklog>>()
+                .await?
+                .into_iter()
+                .next()
+                .map(Into::into)),
+        }
+    }
+
+    #[instrument(level = "trace")]
+    pub async fn get_worklog(&self, id: usize) -> Result<Worklog, TempomatError> {
+        let response = self
+            .request(Method::GET, &format!("worklogs/{id}"))?
+            .send()
+            .await?
+            .error_for_status()?;
+
+        self.parse_worklog(response).await
+    }
+
+    /// Replaces the worklog with the same ID, returns the updated worklog if successful
+    #[instrument(level = "trace")]
+    pub async fn update_worklog(
+        &self,
+        worklog: &Worklog,
+    ) -> Result<Option<Worklog>, TempomatError> {
+        let response = self
+            .request(Method::PUT, &format!("worklogs/{}", worklog.id))?
+            .json(&self.payload(
+                &worklog.author.account_id,
+                &worklog.issue.id.to_string(),
+                worklog.time(),
+                worklog.description.clone(),
+                &worklog.attributes.values,
+            ))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(Some(self.parse_worklog(response).await?))
+        } else {
+            error!(
+                "Got error when updating worklog: {}",
+                response.text().await?
+            );
+            Ok(None)
+        }
+    }
+
+    /// Deletes a worklog, returns true if it was deleted
+    #[instrument(level = "trace")]
+    pub async fn delete_worklog(&self, id: usize) -> Result<bool, TempomatError> {
+        let response = self
+            .request(Method::DELETE, &format!("worklogs/{id}"))?
             .send()
             .await?;
 
@@ -52,10 +165,333 @@ impl<'a> TempoApi<'a> {
             Ok(true)
         } else {
             error!(
-                "Got error when creating worklog: {}",
+                "Got error when deleting worklog: {}",
                 response.text().await?
             );
             Ok(false)
         }
     }
+
+    /// Searches all worklogs of the given user between `from` and `to` (both inclusive)
+    #[instrument(level = "trace")]
+    pub async fn search_worklogs(
+        &self,
+        me: &Myself,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Worklog>, TempomatError> {
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            #[serde(rename = "authorIds")]
+            author_ids: [&'a str; 1],
+            from: NaiveDate,
+            to: NaiveDate,
+        }
+
+        #[derive(Serialize)]
+        struct ServerPayload<'a> {
+            worker: [&'a str; 1],
+            from: NaiveDate,
+            to: NaiveDate,
+        }
+
+        if self.1.deployment == Deployment::Server {
+            // Tempo Server doesn't paginate
+            let worklogs: Vec<ServerWorklog> = self
+                .request(Method::POST, "worklogs/search")?
+                .json(&ServerPayload {
+                    worker: [&me.account_id],
+                    from,
+                    to,
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            return Ok(worklogs.into_iter().map(Into::into).collect());
+        }
+
+        let mut worklogs = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            debug!("Fetching worklogs starting at {offset}");
+            let page: Page<Worklog> = self
+                .request(Method::POST, "worklogs/search")?
+                .query(&[("offset", offset), ("limit", PAGE_LIMIT)])
+                .json(&Payload {
+                    author_ids: [&me.account_id],
+                    from,
+                    to,
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let count = page.results.len();
+            worklogs.extend(page.results);
+
+            if page.metadata.next.is_none() || count == 0 {
+                break;
+            }
+            offset += count;
+        }
+
+        Ok(worklogs)
+    }
+
+    /// Gets the work attributes configured in Tempo
+    #[instrument(level = "trace")]
+    pub async fn get_work_attributes(&self) -> Result<Vec<WorkAttribute>, TempomatError> {
+        Ok(match self.1.deployment {
+            Deployment::Cloud => {
+                self.request(Method::GET, "work-attributes")?
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<Results<WorkAttribute>>()
+                    .await?
+                    .results
+            }
+            // Work attributes are part of Tempo Core on Jira Server
+            Deployment::Server => self
+                .request_url(
+                    Method::GET,
+                    format!("{}/rest/tempo-core/1/work-attribute", self.1.tempo_url()?),
+                )
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Vec<ServerWorkAttribute>>()
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+
+    /// Gets the work schedule of the given user between `from` and `to` (both inclusive)
+    #[instrument(level = "trace")]
+    pub async fn get_user_schedule(
+        &self,
+        me: &Myself,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ScheduleDay>, TempomatError> {
+        let request = match self.1.deployment {
+            Deployment::Cloud => self
+                .request(Method::GET, &format!("user-schedule/{}", me.account_id))?
+                .query(&[("from", from), ("to", to)]),
+            // The schedule is part of Tempo Core on Jira Server
+            Deployment::Server => self
+                .request_url(
+                    Method::GET,
+                    format!("{}/rest/tempo-core/1/user/schedule", self.1.tempo_url()?),
+                )
+                .query(&[("user", me.account_id.as_str())])
+                .query(&[("from", from), ("to", to)]),
+        };
+        let response = request.send().await?.error_for_status()?;
+
+        Ok(match self.1.deployment {
+            Deployment::Cloud => response.json::<Results<ScheduleDay>>().await?.results,
+            Deployment::Server => response.json::<ServerSchedule>().await?.days,
+        })
+    }
+
+    /// Gets the approval of the given user's timesheet for the period `date` is in
+    #[instrument(level = "trace")]
+    pub async fn get_approval(
+        &self,
+        me: &Myself,
+        date: NaiveDate,
+    ) -> Result<TimesheetApproval, TempomatError> {
+        let request = match self.1.deployment {
+            Deployment::Cloud => self
+                .request(
+                    Method::GET,
+                    &format!("timesheet-approvals/user/{}", me.account_id),
+                )?
+                .query(&[("from", date), ("to", date)]),
+            Deployment::Server => self
+                .request(Method::GET, "timesheet-approval/current")?
+                .query(&[("userKey", me.account_id.as_str())])
+                .query(&[("periodStartDate", date)]),
+        };
+        let response = request.send().await?.error_for_status()?;
+
+        Ok(match self.1.deployment {
+            Deployment::Cloud => response.json().await?,
+            Deployment::Server => response.json::<ServerApproval>().await?.into(),
+        })
+    }
+
+    /// Submits the given user's timesheet of a period for approval by `reviewer`
+    #[instrument(level = "trace")]
+    pub async fn submit_timesheet(
+        &self,
+        me: &Myself,
+        period: &ApprovalPeriod,
+        reviewer: &str,
+        comment: Option<String>,
+    ) -> Result<TimesheetApproval, TempomatError> {
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            #[serde(rename = "reviewerAccountId")]
+            reviewer_account_id: &'a str,
+            comment: Option<String>,
+        }
+
+        #[derive(Serialize)]
+        struct ServerKey<'a> {
+            key: &'a str,
+        }
+
+        #[derive(Serialize)]
+        struct ServerPeriod {
+            #[serde(rename = "periodView")]
+            period_view: &'static str,
+            #[serde(rename = "dateFrom")]
+            date_from: NaiveDate,
+        }
+
+        #[derive(Serialize)]
+        struct ServerAction<'a> {
+            name: &'static str,
+            reviewer: ServerKey<'a>,
+            comment: Option<String>,
+        }
+
+        #[derive(Serialize)]
+        struct ServerPayload<'a> {
+            user: ServerKey<'a>,
+            period: ServerPeriod,
+            action: ServerAction<'a>,
+        }
+
+        let request = match self.1.deployment {
+            Deployment::Cloud => self
+                .request(
+                    Method::POST,
+                    &format!("timesheet-approvals/user/{}/submit", me.account_id),
+                )?
+                .query(&[("from", period.from), ("to", period.to)])
+                .json(&Payload {
+                    reviewer_account_id: reviewer,
+                    comment,
+                }),
+            Deployment::Server => {
+                self.request(Method::POST, "timesheet-approval")?
+                    .json(&ServerPayload {
+                        user: ServerKey {
+                            key: &me.account_id,
+                        },
+                        period: ServerPeriod {
+                            period_view: "PERIOD",
+                            date_from: period.from,
+                        },
+                        action: ServerAction {
+                            name: "submit",
+                            reviewer: ServerKey { key: reviewer },
+                            comment,
+                        },
+                    })
+            }
+        };
+        let response = request.send().await?.error_for_status()?;
+
+        Ok(match self.1.deployment {
+            Deployment::Cloud => response.json().await?,
+            Deployment::Server => response.json::<ServerApproval>().await?.into(),
+        })
+    }
+
+    /// The JSON payload a worklog would be submitted with, used to preview submissions
+    pub fn preview_payload(
+        &self,
+        author: &str,
+        issue_id: &str,
+        time: WorklogTime,
+        description: Option<String>,
+        attributes: &[AttributeValue],
+    ) -> Result<serde_json::Value, TempomatError> {
+        Ok(serde_json::to_value(self.payload(
+            author,
+            issue_id,
+            time,
+            description,
+            attributes,
+        ))?)
+    }
+
+    /// Creates an authenticated request, `path` is relative to the worklog API root
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder, TempomatError> {
+        let url = match self.1.deployment {
+            Deployment::Cloud => format!("{}/4/{path}", self.1.tempo_url()?),
+            Deployment::Server => {
+                format!("{}/rest/tempo-timesheets/4/{path}", self.1.tempo_url()?)
+            }
+        };
+
+        Ok(self.request_url(method, url))
+    }
+
+    fn request_url(&self, method: Method, url: String) -> RequestBuilder {
+        http_client().request(method, url).bearer_auth(self.0)
+    }
+
+    fn payload<'p>(
+        &self,
+        author: &'p str,
+        issue_id: &'p str,
+        time: WorklogTime,
+        description: Option<String>,
+        attributes: &'p [AttributeValue],
+    ) -> WorklogPayload<'p> {
+        match self.1.deployment {
+            Deployment::Cloud => WorklogPayload::Cloud(CloudWorklogPayload {
+                author_account_id: author,
+                issue_id,
+                time_spent: time.time_spent,
+                billable: time.billable,
+                remaining: time.remaining,
+                start_date: time.start.date(),
+                start_time: time.start.time().format("%H:%M:%S").to_string(),
+                description,
+                attributes,
+            }),
+            Deployment::Server => WorklogPayload::Server(ServerWorklogPayload {
+                worker: author,
+                origin_task_id: issue_id,
+                started: time.start.format("%Y-%m-%dT%H:%M:%S%.3f").to_string(),
+                time_spent: time.time_spent,
+                billable: time.billable,
+                remaining: time.remaining,
+                comment: description,
+                attributes: attributes
+                    .iter()
+                    .map(|attribute| {
+                        (
+                            attribute.key.as_str(),
+                            ServerAttributePayload {
+                                work_attribute_id: attribute.work_attribute_id,
+                                value: &attribute.value,
+                            },
+                        )
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    async fn parse_worklog(&self, response: Response) -> Result<Worklog, TempomatError> {
+        match self.1.deployment {
+            Deployment::Cloud => Ok(response.json().await?),
+            Deployment::Server => Ok(response.json::<ServerWorklog>().await?.into()),
+        }
+    }
 }
@@ -1,5 +1,4 @@
-use crate::error::TempomatError;
-use reqwest::Client;
+use crate::{error::TempomatError, http_client};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument};
 
@@ -9,8 +8,8 @@ const CLIENT_SECRET: &str = "0A339C40026062C9EC06DBB01948B053C46B6888A1D0450E585
 const OAUTH_SERVER_PORT: u16 = 8734;
 pub const OAUTH_REDIRECT_URI: &str = "http://127.0.0.1:8734/cb";
 
-pub fn generate_access_link(instance: &str, redirect: &str) -> String {
-    format!("https://{instance}.atlassian.net/plugins/servlet/ac/io.tempo.jira/oauth-authorize/?client_id={CLIENT_ID}&redirect_uri={redirect}")
+pub fn generate_access_link(jira_url: &str, redirect: &str) -> String {
+    format!("{jira_url}/plugins/servlet/ac/io.tempo.jira/oauth-authorize/?client_id={CLIENT_ID}&redirect_uri={redirect}")
 }
 
 /// OAuth tokens for tempo
@@ -58,9 +57,8 @@ impl GetAccessTokens {
 
     #[instrument(level = "trace")]
     pub async fn get_tokens(&self) -> Result<TempoAccessTokens, TempomatError> {
-        let client = Client::new();
         debug!("Sending request to get OAuth tokens...");
-        let response = client
+        let response = http_client()
             .post("https://api.tempo.io/oauth/token")
             .form(self)
             .send()
@@ -82,8 +80,7 @@ impl TempoAccessTokens {
             token: String,
         }
 
-        let client = Client::new();
-        let response = client
+        let response = http_client()
             .post("https://api.tempo.io/oauth/revoke_token/")
             .form(&RequestTokenRemove {
                 token_type_hint: "refresh_token",
@@ -95,7 +92,7 @@ impl TempoAccessTokens {
             .await?;
 
         if !response.status().is_success() {
-            Err(TempomatError::OAuthRevokeFailed(response))?
+            Err(TempomatError::OAuthRevokeFailed(Box::new(response)))?
         }
 
         Ok(())
@@ -115,10 +112,10 @@ pub mod actions {
     pub async fn login(config: &Config) -> Result<TempoAccessTokens, TempomatError> {
         // Start a server in the background
         let server = server::get_code(([127, 0, 0, 1], OAUTH_SERVER_PORT).into());
-        let link = generate_access_link(&config.atlassian_instance, OAUTH_REDIRECT_URI);
+        let link = generate_access_link(&config.jira_url()?, OAUTH_REDIRECT_URI);
         // Start the oauth process by opening the initial link in the browser
         let _ = open::that(&link);
-        println!("Click \"Accept\" and then \"Onwards\" in your browser tab, if nothing happened click this link: {link}");
+        eprintln!("Click \"Accept\" and then \"Onwards\" in your browser tab, if nothing happened click this link: {link}");
 
         let code = server.await?;
 
@@ -0,0 +1,418 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{collections::HashMap, fmt};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Worklog {
+    #[serde(rename = "tempoWorklogId")]
+    pub id: usize,
+    pub issue: WorklogIssue,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent: usize,
+    /// Tempo resets it to the time spent if it isn't sent back on updates
+    #[serde(rename = "billableSeconds", default)]
+    pub billable: Option<usize>,
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[serde(rename = "startTime")]
+    pub start_time: NaiveTime,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub author: WorklogAuthor,
+    #[serde(default)]
+    pub attributes: WorklogAttributes,
+}
+
+/// When and how long was worked, in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorklogTime {
+    pub start: NaiveDateTime,
+    pub time_spent: usize,
+    /// Defaults to the time spent in Tempo
+    pub billable: Option<usize>,
+    /// New remaining estimate of the issue, Tempo adjusts it automatically if not set
+    pub remaining: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct WorklogAttributes {
+    #[serde(default)]
+    pub values: Vec<AttributeValue>,
+}
+
+/// The value of a work attribute on a worklog
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AttributeValue {
+    pub key: String,
+    pub value: String,
+    /// Needed to submit the value to Tempo Server
+    #[serde(skip)]
+    pub work_attribute_id: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorklogIssue {
+    pub id: usize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorklogAuthor {
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+}
+
+/// A paginated response from the Tempo API
+#[derive(Deserialize, Debug)]
+pub struct Page<T> {
+    pub metadata: PageMetadata,
+    pub results: Vec<T>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PageMetadata {
+    pub count: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub next: Option<String>,
+}
+
+/// A response from the Tempo API that is not paginated
+#[derive(Deserialize, Debug)]
+pub struct Results<T> {
+    pub results: Vec<T>,
+}
+
+/// A day of a user's work schedule
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScheduleDay {
+    pub date: NaiveDate,
+    #[serde(rename = "requiredSeconds")]
+    pub required_seconds: usize,
+}
+
+/// A user's work schedule as returned by Tempo Core on Jira Server
+#[derive(Deserialize, Debug)]
+pub struct ServerSchedule {
+    pub days: Vec<ScheduleDay>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WorkAttributeType {
+    Account,
+    StaticList,
+    Checkbox,
+    InputField,
+    InputNumeric,
+    #[serde(other)]
+    Other,
+}
+
+/// A work attribute configured in Tempo
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WorkAttribute {
+    pub key: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: WorkAttributeType,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values of a static list
+    #[serde(default)]
+    pub values: Vec<String>,
+    /// Names of the static list values
+    #[serde(default)]
+    pub names: HashMap<String, String>,
+    /// Only set by Tempo Server
+    #[serde(default)]
+    pub id: Option<usize>,
+}
+
+/// A work attribute as returned by Tempo Core on Jira Server
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerWorkAttribute {
+    pub id: usize,
+    pub key: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ServerWorkAttributeType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(rename = "staticListValues", default)]
+    pub static_list_values: Vec<ServerStaticListValue>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerWorkAttributeType {
+    pub value: WorkAttributeType,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerStaticListValue {
+    pub value: String,
+    pub name: String,
+}
+
+impl From<ServerWorkAttribute> for WorkAttribute {
+    fn from(attribute: ServerWorkAttribute) -> Self {
+        Self {
+            key: attribute.key,
+            name: attribute.name,
+            kind: attribute.kind.value,
+            required: attribute.required,
+            values: attribute
+                .static_list_values
+                .iter()
+                .map(|value| value.value.clone())
+                .collect(),
+            names: attribute
+                .static_list_values
+                .into_iter()
+                .map(|value| (value.value, value.name))
+                .collect(),
+            id: Some(attribute.id),
+        }
+    }
+}
+
+/// A work attribute value of a worklog on Tempo Server
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerAttributeValue {
+    #[serde(rename = "workAttributeId", default)]
+    pub work_attribute_id: Option<usize>,
+    pub value: String,
+}
+
+/// Approval status of a timesheet
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Open,
+    /// Submitted and waiting for the reviewer
+    InReview,
+    Approved,
+    Rejected,
+    Unknown,
+}
+
+impl From<String> for ApprovalStatus {
+    /// Tempo Cloud uses keys like `IN_REVIEW`, Tempo Server ones like `waiting_for_approval`
+    fn from(key: String) -> Self {
+        match key.to_lowercase().as_str() {
+            "open" | "ready_to_submit" => Self::Open,
+            "in_review" | "waiting_for_approval" => Self::InReview,
+            "approved" => Self::Approved,
+            "rejected" => Self::Rejected,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for ApprovalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Open => "open",
+            Self::InReview => "in review",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApprovalPeriod {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Deserialize)]
+struct ApprovalStatusInfo {
+    key: String,
+}
+
+/// The approval of a user's timesheet for a period
+#[derive(Deserialize, Debug, Clone)]
+pub struct TimesheetApproval {
+    pub period: ApprovalPeriod,
+    #[serde(deserialize_with = "deserialize_cloud_status")]
+    pub status: ApprovalStatus,
+    #[serde(default)]
+    pub reviewer: Option<WorklogAuthor>,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent: usize,
+    #[serde(rename = "requiredSeconds")]
+    pub required: usize,
+}
+
+fn deserialize_cloud_status<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<ApprovalStatus, D::Error> {
+    Ok(ApprovalStatusInfo::deserialize(deserializer)?.key.into())
+}
+
+/// A timesheet approval as returned by Tempo Timesheets on Jira Server
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerApproval {
+    pub period: ServerApprovalPeriod,
+    pub status: String,
+    #[serde(default)]
+    pub reviewer: Option<ServerUser>,
+    #[serde(rename = "workedSeconds")]
+    pub worked: usize,
+    #[serde(rename = "requiredSeconds")]
+    pub required: usize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerApprovalPeriod {
+    #[serde(rename = "dateFrom")]
+    pub from: NaiveDate,
+    #[serde(rename = "dateTo")]
+    pub to: NaiveDate,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerUser {
+    pub key: String,
+}
+
+impl From<ServerApproval> for TimesheetApproval {
+    fn from(approval: ServerApproval) -> Self {
+        Self {
+            period: ApprovalPeriod {
+                from: approval.period.from,
+                to: approval.period.to,
+            },
+            status: approval.status.into(),
+            reviewer: approval.reviewer.map(|reviewer| WorklogAuthor {
+                account_id: reviewer.key,
+            }),
+            time_spent: approval.worked,
+            required: approval.required,
+        }
+    }
+}
+
+/// A worklog as returned by Tempo Timesheets on Jira Server
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerWorklog {
+    #[serde(rename = "tempoWorklogId")]
+    pub id: usize,
+    pub issue: WorklogIssue,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent: usize,
+    #[serde(rename = "billableSeconds", default)]
+    pub billable: Option<usize>,
+    #[serde(deserialize_with = "deserialize_server_date")]
+    pub started: NaiveDateTime,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// User key of the author
+    pub worker: String,
+    #[serde(default)]
+    pub attributes: HashMap<String, ServerAttributeValue>,
+}
+
+/// Tempo Server formats dates like `2023-04-10 09:00:00.000`
+fn deserialize_server_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<NaiveDateTime, D::Error> {
+    let date = String::deserialize(deserializer)?;
+
+    NaiveDateTime::parse_from_str(&date, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S%.f"))
+        .map_err(serde::de::Error::custom)
+}
+
+impl From<ServerWorklog> for Worklog {
+    fn from(worklog: ServerWorklog) -> Self {
+        Self {
+            id: worklog.id,
+            issue: worklog.issue,
+            time_spent: worklog.time_spent,
+            billable: worklog.billable,
+            start_date: worklog.started.date(),
+            start_time: worklog.started.time(),
+            description: worklog.comment,
+            author: WorklogAuthor {
+                account_id: worklog.worker,
+            },
+            attributes: WorklogAttributes {
+                values: worklog
+                    .attributes
+                    .into_iter()
+                    .map(|(key, value)| AttributeValue {
+                        key,
+                        value: value.value,
+                        work_attribute_id: value.work_attribute_id,
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl Worklog {
+    pub fn start(&self) -> NaiveDateTime {
+        self.start_date.and_time(self.start_time)
+    }
+
+    /// The time of the worklog with its billable time, the estimate is left to Tempo
+    pub fn time(&self) -> WorklogTime {
+        WorklogTime {
+            start: self.start(),
+            time_spent: self.time_spent,
+            billable: self.billable,
+            remaining: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ApprovalStatus, ServerApproval, ServerWorklog, TimesheetApproval, Worklog};
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_server_worklog() {
+        let worklog: ServerWorklog = ron::from_str(
+            r#"(tempoWorklogId:12,issue:(id:10005),timeSpentSeconds:5400,billableSeconds:Some(1800),started:"2023-04-10 09:30:00.000",worker:"JIRAUSER10000")"#,
+        )
+        .unwrap();
+        let worklog = Worklog::from(worklog);
+
+        assert_eq!(worklog.id, 12);
+        assert_eq!(worklog.issue.id, 10005);
+        assert_eq!(
+            worklog.start_date,
+            NaiveDate::from_ymd_opt(2023, 4, 10).unwrap()
+        );
+        assert_eq!(
+            worklog.start_time,
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap()
+        );
+        assert_eq!(worklog.author.account_id, "JIRAUSER10000");
+        assert!(worklog.description.is_none());
+        assert_eq!(worklog.time().billable, Some(1800));
+    }
+
+    #[test]
+    fn test_approval_status() {
+        let approval: TimesheetApproval = ron::from_str(
+            r#"(period:(from:"2023-04-01",to:"2023-04-30"),status:(key:"IN_REVIEW"),reviewer:Some((accountId:"123")),timeSpentSeconds:3600,requiredSeconds:7200)"#,
+        )
+        .unwrap();
+        assert_eq!(approval.status, ApprovalStatus::InReview);
+        assert_eq!(approval.reviewer.unwrap().account_id, "123");
+
+        let approval: ServerApproval = ron::from_str(
+            r#"(period:(dateFrom:"2023-04-01",dateTo:"2023-04-30"),status:"waiting_for_approval",workedSeconds:3600,requiredSeconds:7200)"#,
+        )
+        .unwrap();
+        let approval = TimesheetApproval::from(approval);
+        assert_eq!(approval.status, ApprovalStatus::InReview);
+        assert!(approval.reviewer.is_none());
+        assert_eq!(approval.time_spent, 3600);
+    }
+}
@@ -1,3 +1,8 @@
+use crate::error::TempomatError;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
+
 pub fn seconds_to_string(seconds: usize) -> String {
     let mut time_str = String::new();
 
@@ -16,3 +21,114 @@ pub fn seconds_to_string(seconds: usize) -> String {
 
     time_str
 }
+
+/// Formats a difference in time with its sign, eg. `-1h30m` or `+15m`
+pub fn balance_to_string(seconds: i64) -> String {
+    match seconds {
+        0 => "0m".to_string(),
+        ..=-1 => format!("-{}", seconds_to_string(seconds.unsigned_abs() as usize)),
+        _ => format!("+{}", seconds_to_string(seconds as usize)),
+    }
+}
+
+/// Returns the first (monday) and last (sunday) day of the week `date` is in
+pub fn week_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+
+    (monday, monday + Duration::days(6))
+}
+
+/// The instant a local time refers to in `tz`. Fails for times that a daylight saving time change
+/// skips, or repeats so that they could mean two instants
+pub fn local_instant<Tz: TimeZone>(
+    tz: &Tz,
+    time: NaiveDateTime,
+) -> Result<DateTime<Tz>, TempomatError> {
+    match tz.from_local_datetime(&time) {
+        LocalResult::Single(instant) => Ok(instant),
+        LocalResult::None => Err(TempomatError::NonexistentLocalTime(time)),
+        LocalResult::Ambiguous(..) => Err(TempomatError::AmbiguousLocalTime(time)),
+    }
+}
+
+/// Start and length in seconds of the time from `from` to `to` on `date`, measured between the
+/// instants so that a daylight saving time change in between is respected
+pub fn time_range<Tz: TimeZone>(
+    tz: &Tz,
+    date: NaiveDate,
+    from: NaiveTime,
+    to: NaiveTime,
+) -> Result<(DateTime<Tz>, usize), TempomatError> {
+    let start = local_instant(tz, date.and_time(from))?;
+    let end = local_instant(tz, date.and_time(to))?;
+    let seconds = (end - start.clone()).num_seconds();
+    if seconds <= 0 {
+        Err(TempomatError::InvalidTimeRange)?
+    }
+
+    Ok((start, seconds as usize))
+}
+
+/// Checks that a worklog doesn't end in the future
+pub fn validate_worklog<Tz: TimeZone>(
+    start: &DateTime<Tz>,
+    seconds: usize,
+    now: &DateTime<Tz>,
+) -> Result<(), TempomatError> {
+    if start.clone() + Duration::seconds(seconds as i64) > *now {
+        Err(TempomatError::WorklogInFuture)?
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{balance_to_string, local_instant, time_range, validate_worklog, week_range};
+    use chrono::{Duration, NaiveDate, NaiveTime};
+    use chrono_tz::Europe::Berlin;
+
+    #[test]
+    fn test_week_range() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+
+        assert_eq!(week_range(date(14)), (date(12), date(18)));
+        assert_eq!(week_range(date(12)), (date(12), date(18)));
+        assert_eq!(week_range(date(18)), (date(12), date(18)));
+    }
+
+    #[test]
+    fn test_daylight_saving_time() {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        // Clocks went from 02:00 to 03:00 in spring, and from 03:00 back to 02:00 in autumn
+        let spring = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        let autumn = NaiveDate::from_ymd_opt(2026, 10, 25).unwrap();
+
+        assert!(local_instant(&Berlin, spring.and_time(time(2, 30))).is_err());
+        assert!(local_instant(&Berlin, autumn.and_time(time(2, 30))).is_err());
+
+        let (start, seconds) = time_range(&Berlin, spring, time(1, 30), time(3, 30)).unwrap();
+        assert_eq!(start.naive_local(), spring.and_time(time(1, 30)));
+        assert_eq!(seconds, 3600);
+        let (_, seconds) = time_range(&Berlin, autumn, time(1, 30), time(3, 30)).unwrap();
+        assert_eq!(seconds, 3 * 3600);
+        assert!(time_range(&Berlin, spring, time(3, 30), time(1, 30)).is_err());
+
+        // 3 hours from 01:30 in autumn end at 03:30, not at 04:30
+        let start = local_instant(&Berlin, autumn.and_time(time(1, 30))).unwrap();
+        let now = local_instant(&Berlin, autumn.and_time(time(3, 45))).unwrap();
+        assert!(validate_worklog(&start, 3 * 3600, &now).is_ok());
+        assert!(validate_worklog(&start, 3 * 3600 + 30 * 60, &now).is_err());
+        assert_eq!(
+            (now - Duration::hours(3)).naive_local(),
+            autumn.and_time(time(1, 45))
+        );
+    }
+
+    #[test]
+    fn test_balance_to_string() {
+        assert_eq!(balance_to_string(-5400), "-1h30m");
+        assert_eq!(balance_to_string(900), "+15m");
+        assert_eq!(balance_to_string(0), "0m");
+    }
+}
@@ -1,13 +1,195 @@
 use crate::config::Saveable;
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Default)]
-pub struct TempoTimers(pub HashMap<String, NaiveDateTime>);
+pub struct TempoTimers(pub HashMap<String, Timer>);
+
+impl TempoTimers {
+    /// Issue keys of all running (not paused) timers, sorted
+    pub fn running(&self) -> Vec<&str> {
+        let mut running: Vec<_> = self
+            .0
+            .iter()
+            .filter(|(_, timer)| timer.is_running())
+            .map(|(issue, _)| issue.as_str())
+            .collect();
+        running.sort();
+
+        running
+    }
+
+    /// Starts a timer for `issue` or resumes its paused one, keeping the time recorded so far
+    pub fn start(&mut self, issue: &str, now: NaiveDateTime) -> Started {
+        match self.0.get_mut(issue) {
+            Some(timer) if timer.is_running() => Started::AlreadyRunning,
+            Some(timer) => {
+                timer.resume(now);
+                Started::Resumed
+            }
+            None => {
+                self.0.insert(issue.to_string(), Timer::new(now));
+                Started::New
+            }
+        }
+    }
+}
+
+/// Outcome of [`TempoTimers::start`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Started {
+    New,
+    Resumed,
+    AlreadyRunning,
+}
+
+/// A period of time in which a timer was running
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub start: NaiveDateTime,
+    /// Not set while the timer is running
+    pub end: Option<NaiveDateTime>,
+}
+
+/// A timer, only the time in its segments is counted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(from = "StoredTimer")]
+pub struct Timer {
+    pub segments: Vec<Segment>,
+}
+
+/// Timers used to only store their start time, those are migrated when read
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTimer {
+    Segments { segments: Vec<Segment> },
+    Started(NaiveDateTime),
+}
+
+impl Timer {
+    pub fn new(start: NaiveDateTime) -> Self {
+        Self {
+            segments: vec![Segment { start, end: None }],
+        }
+    }
+
+    /// When the timer was first started
+    pub fn start(&self) -> Option<NaiveDateTime> {
+        self.segments.first().map(|segment| segment.start)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.segments
+            .last()
+            .is_some_and(|segment| segment.end.is_none())
+    }
+
+    /// Pauses the timer, returns false if it was already paused
+    pub fn pause(&mut self, now: NaiveDateTime) -> bool {
+        match self.segments.last_mut() {
+            Some(segment) if segment.end.is_none() => {
+                segment.end = Some(now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Resumes the timer, returns false if it was already running
+    pub fn resume(&mut self, now: NaiveDateTime) -> bool {
+        if self.is_running() {
+            false
+        } else {
+            self.segments.push(Segment {
+                start: now,
+                end: None,
+            });
+            true
+        }
+    }
+
+    /// Sum of the time in all segments, a running segment counts until `now`
+    pub fn elapsed(&self, now: NaiveDateTime) -> Duration {
+        self.segments
+            .iter()
+            .map(|segment| segment.end.unwrap_or(now) - segment.start)
+            .fold(Duration::zero(), |total, segment| total + segment)
+    }
+}
+
+impl From<StoredTimer> for Timer {
+    fn from(timer: StoredTimer) -> Self {
+        match timer {
+            StoredTimer::Segments { segments } => Self { segments },
+            StoredTimer::Started(start) => Self::new(start),
+        }
+    }
+}
 
 impl Saveable for TempoTimers {
     fn path(root: &std::path::Path) -> std::path::PathBuf {
         root.join("timers.ron")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Started, TempoTimers, Timer};
+    use crate::config::ron_options;
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 4, 10)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let mut timer = Timer::new(at(9, 0));
+
+        assert!(timer.pause(at(12, 0)));
+        assert!(!timer.pause(at(12, 30)));
+        assert!(!timer.is_running());
+        assert_eq!(timer.elapsed(at(13, 0)), Duration::hours(3));
+
+        assert!(timer.resume(at(13, 0)));
+        assert!(!timer.resume(at(13, 30)));
+        assert_eq!(timer.elapsed(at(14, 30)), Duration::minutes(4 * 60 + 30));
+        assert_eq!(timer.start(), Some(at(9, 0)));
+    }
+
+    #[test]
+    fn test_start_paused_timer() {
+        let mut timers = TempoTimers::default();
+        assert_eq!(timers.start("DV-1", at(9, 0)), Started::New);
+        timers.0.get_mut("DV-1").unwrap().pause(at(10, 0));
+
+        // Starting a paused timer resumes it instead of discarding its segments
+        assert_eq!(timers.start("DV-1", at(11, 0)), Started::Resumed);
+        assert_eq!(timers.0["DV-1"].segments.len(), 2);
+        assert_eq!(timers.0["DV-1"].elapsed(at(12, 0)), Duration::hours(2));
+
+        assert_eq!(timers.start("DV-1", at(12, 0)), Started::AlreadyRunning);
+        assert_eq!(timers.0["DV-1"].segments.len(), 2);
+    }
+
+    #[test]
+    fn test_migrate_timers() {
+        let timers: TempoTimers = ron_options()
+            .from_str(r#"({"DV-1":"2023-04-10T09:00:00"})"#)
+            .unwrap();
+        assert_eq!(timers.0["DV-1"], Timer::new(at(9, 0)));
+
+        let mut timer = Timer::new(at(9, 0));
+        timer.pause(at(10, 0));
+        timer.resume(at(11, 0));
+        let saved = ron_options()
+            .to_string(&TempoTimers([("DV-1".to_string(), timer.clone())].into()))
+            .unwrap();
+        let timers: TempoTimers = ron_options().from_str(&saved).unwrap();
+        assert_eq!(timers.0["DV-1"], timer);
+    }
+}